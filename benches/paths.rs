@@ -0,0 +1,79 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use thumbor::{__bench, Filter, Server};
+
+fn paths(c: &mut Criterion) {
+    let server = Server::new("http://localhost:8888", "my-security-key").expect("valid key");
+    let endpoint = server
+        .endpoint_builder()
+        .resize((300, 200))
+        .smart(true)
+        .filters([Filter::Brightness(10), Filter::Contrast(20)])
+        .build();
+
+    let image_uris = (0..10_000)
+        .map(|i| format!("path/to/my/image-{i}.jpg"))
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("10k paths");
+
+    group.bench_function("to_path", |b| {
+        b.iter(|| {
+            for image_uri in &image_uris {
+                black_box(endpoint.to_path(image_uri));
+            }
+        });
+    });
+
+    group.bench_function("paths_for", |b| {
+        b.iter(|| {
+            for path in endpoint.paths_for(image_uris.iter().map(String::as_str)) {
+                black_box(path);
+            }
+        });
+    });
+
+    group.finish();
+}
+
+fn resize_only(c: &mut Criterion) {
+    let server = Server::new("http://localhost:8888", "my-security-key").expect("valid key");
+    let endpoint = server.endpoint_builder().resize((300, 200)).build();
+
+    let mut group = c.benchmark_group("resize only");
+
+    group.bench_function("fast path", |b| {
+        b.iter(|| black_box(__bench::resize_only_path(&endpoint)));
+    });
+
+    group.bench_function("generic path", |b| {
+        b.iter(|| black_box(__bench::generic_path(&endpoint)));
+    });
+
+    group.finish();
+}
+
+fn long_pipeline(c: &mut Criterion) {
+    let server = Server::new("http://localhost:8888", "my-security-key").expect("valid key");
+    let endpoint = server
+        .endpoint_builder()
+        .resize((300, 200))
+        .filters((0..24).map(|i| {
+            if i % 2 == 0 {
+                Filter::Brightness(i)
+            } else {
+                Filter::watermark(format!("http://example.com/logo-{i}.png"), 10, 20, 50)
+            }
+        }))
+        .build();
+
+    let mut group = c.benchmark_group("24 filters");
+
+    group.bench_function("to_path", |b| {
+        b.iter(|| black_box(endpoint.to_path("path/to/my/image.jpg")));
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, paths, resize_only, long_pipeline);
+criterion_main!(benches);
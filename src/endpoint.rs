@@ -1,17 +1,40 @@
-use std::fmt::Display;
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::{
+    fmt::{Display, Write},
+    str::FromStr,
+};
 
+use crate::filter::{Color, Filter};
 use crate::{
-    geometry::{Point, Rect},
+    error::Error,
+    geometry::{CropRect, Dimension, Rect, Resize},
     metadata::Operation,
     server::Server,
 };
-use endpoint_builder::{IsUnset, SetResponse, State};
-use filter::Filter;
+use endpoint_builder::{IsUnset, SetFitIn, SetResize, SetResponse, SetTrim, State};
+
+pub use builder::CompiledEndpoint;
+pub use segment::Segment;
+pub use signature::Signature;
+pub use template::EndpointTemplate;
+pub use validate::Warning;
 
 mod builder;
-pub mod filter;
+#[cfg(feature = "reqwest")]
+mod fetch;
+mod parser;
+mod segment;
+mod signature;
+mod template;
+mod validate;
 
-#[derive(strum::Display)]
+/// Deprecated alias of [`thumbor::filter`](crate::filter).
+#[deprecated(since = "0.1.12", note = "import from `thumbor::filter` instead")]
+pub mod filter {
+    pub use crate::filter::*;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, strum::EnumString)]
 #[strum(serialize_all = "lowercase")]
 pub enum HAlignment {
     Left,
@@ -19,7 +42,7 @@ pub enum HAlignment {
     Right,
 }
 
-#[derive(strum::Display)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, strum::EnumString)]
 #[strum(serialize_all = "lowercase")]
 pub enum VAlignment {
     Top,
@@ -27,16 +50,82 @@ pub enum VAlignment {
     Bottom,
 }
 
-#[derive(Default, strum::Display)]
-pub enum Trim {
+/// The pixel whose color is trimmed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, strum::Display, strum::EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub enum TrimOrigin {
     #[default]
-    #[strum(to_string = "trim:top-left")]
     TopLeft,
-    #[strum(to_string = "trim:bottom-right")]
     BottomRight,
 }
 
-#[derive(Default, strum::Display)]
+/// ```
+/// use thumbor::endpoint::{Trim, TrimOrigin};
+///
+/// let trim = Trim { origin: TrimOrigin::BottomRight, tolerance: 15 };
+///
+/// assert_eq!(trim.to_string(), "trim:bottom-right:15");
+/// assert_eq!("trim:bottom-right:15".parse::<Trim>().ok(), Some(trim));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Trim {
+    pub origin: TrimOrigin,
+    /// The euclidean distance between colors under which they are trimmed, within 0-442 for RGB images.
+    pub tolerance: u16,
+}
+
+impl From<TrimOrigin> for Trim {
+    fn from(origin: TrimOrigin) -> Self {
+        Self {
+            origin,
+            tolerance: 0,
+        }
+    }
+}
+
+impl Display for Trim {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "trim:{}", self.origin)?;
+        if self.tolerance > 0 {
+            write!(f, ":{}", self.tolerance)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Trim {
+    type Err = Error;
+
+    /// Parses `trim[:origin][:tolerance]`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::invalid_segment("trim", s);
+
+        let mut parts = s.split(':');
+        if parts.next() != Some("trim") {
+            return Err(invalid());
+        }
+
+        let mut trim = Trim::default();
+        let mut next = parts.next();
+        if let Some(origin) = next.and_then(|origin| origin.parse().ok()) {
+            trim.origin = origin;
+            next = parts.next();
+        }
+        if let Some(tolerance) = next {
+            if !tolerance.bytes().all(|byte| byte.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            trim.tolerance = tolerance.parse().map_err(|_| invalid())?;
+        }
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+
+        Ok(trim)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, strum::Display, strum::EnumString)]
 pub enum FitIn {
     #[default]
     #[strum(to_string = "fit-in")]
@@ -45,41 +134,142 @@ pub enum FitIn {
     Adaptive,
     #[strum(to_string = "full-fit-in")]
     Full,
+    #[strum(to_string = "adaptive-full-fit-in")]
+    AdaptiveFull,
+}
+
+impl FitIn {
+    /// The adaptive variant of this fit-in.
+    ///
+    /// An adaptive fit-in swaps the requested width and height when the image has the other
+    /// orientation, so that a portrait image requested as `300x200` fits in `200x300` instead
+    /// of being shrunk to fit the landscape box. This helps for galleries mixing orientations.
+    ///
+    /// ```
+    /// use thumbor::endpoint::FitIn;
+    ///
+    /// assert_eq!(FitIn::Default.adaptive(), FitIn::Adaptive);
+    /// assert_eq!(FitIn::Full.adaptive(), FitIn::AdaptiveFull);
+    /// ```
+    #[must_use]
+    pub fn adaptive(self) -> Self {
+        match self {
+            FitIn::Default | FitIn::Adaptive => FitIn::Adaptive,
+            FitIn::Full | FitIn::AdaptiveFull => FitIn::AdaptiveFull,
+        }
+    }
+}
+
+/// How the image URI is written in the path.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UriEncoding {
+    /// The image URI is written as is.
+    ///
+    /// A query string (`?v=2`) or a fragment would then be read as part of the Thumbor URL
+    /// instead of being forwarded to the origin of the image.
+    #[default]
+    None,
+    /// The image URI is percent-encoded, except for unreserved characters, `/` and `:`.
+    /// Non-ASCII characters are encoded as their UTF-8 bytes.
+    ///
+    /// Thumbor decodes it before loading the image, so query strings reach the origin intact.
+    Encode,
+    /// The image URI is already percent-encoded: its `%XX` escapes are kept as is, and only
+    /// the other characters that [`UriEncoding::Encode`] would encode are encoded.
+    ///
+    /// Use it instead of [`UriEncoding::Encode`] to avoid double-encoding, which would
+    /// turn `%20` into `%2520`.
+    AlreadyEncoded,
+}
+
+impl UriEncoding {
+    pub(crate) fn apply(self, image_uri: &str) -> Cow<'_, str> {
+        let bytes = image_uri.as_bytes();
+        let is_kept = |i: usize| {
+            let byte = bytes[i];
+            byte.is_ascii_alphanumeric()
+                || b"-._~/:".contains(&byte)
+                || (self == UriEncoding::AlreadyEncoded
+                    && byte == b'%'
+                    && bytes
+                        .get(i + 1..i + 3)
+                        .map_or(false, |escape| escape.iter().all(u8::is_ascii_hexdigit)))
+        };
+
+        match self {
+            UriEncoding::Encode | UriEncoding::AlreadyEncoded if !(0..bytes.len()).all(is_kept) => {
+                let mut encoded = String::with_capacity(image_uri.len());
+                for (i, &byte) in bytes.iter().enumerate() {
+                    if is_kept(i) {
+                        encoded.push(char::from(byte));
+                    } else {
+                        const HEX: &[u8; 16] = b"0123456789ABCDEF";
+                        encoded.push('%');
+                        encoded.push(char::from(HEX[usize::from(byte >> 4)]));
+                        encoded.push(char::from(HEX[usize::from(byte & 0xF)]));
+                    }
+                }
+                Cow::Owned(encoded)
+            }
+            _ => Cow::Borrowed(image_uri),
+        }
+    }
 }
 
 struct Smart;
 
 impl Display for Smart {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "smart")
     }
 }
 
-struct Filters<'a>(&'a [Filter]);
+struct Filters<'a> {
+    filters: Vec<&'a Filter>,
+    short_hex: bool,
+}
 
 impl<'a> Filters<'a> {
-    fn new(filters: &'a [Filter]) -> Option<Self> {
+    fn new(filters: &'a [Filter], sort: bool, dedup: bool, short_hex: bool) -> Option<Self> {
         if filters.is_empty() {
-            None
-        } else {
-            Some(Self(filters))
+            return None;
         }
+
+        let mut filters = filters
+            .iter()
+            .enumerate()
+            .filter(|(i, filter)| {
+                !dedup
+                    || !filter.is_single_valued()
+                    || !filters[i + 1..]
+                        .iter()
+                        .any(|next| next.name() == filter.name())
+            })
+            .map(|(_, filter)| filter)
+            .collect::<Vec<_>>();
+        if sort {
+            filters.sort_by_key(|filter| filter.name());
+        }
+        Some(Self { filters, short_hex })
     }
 }
 
 impl Display for Filters<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let filters = self
-            .0
-            .iter()
-            .map(ToString::to_string)
-            .collect::<Vec<_>>()
-            .join(":");
-        write!(f, "filters:{filters}")
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // Written directly into the formatter, as long pipelines would otherwise allocate
+        // a string per filter and per argument.
+        f.write_str("filters:")?;
+        for (i, filter) in self.filters.iter().enumerate() {
+            if i > 0 {
+                f.write_char(':')?;
+            }
+            filter.write_with(f, self.short_hex)?;
+        }
+        Ok(())
     }
 }
 
-#[derive(strum::Display)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, strum::EnumString)]
 pub enum ResponseMode {
     /// The metadata endpoint has **ALL** the options that the image one has,
     /// but instead of actually performing the operations in the image, it just simulates the operations.
@@ -140,6 +330,23 @@ pub enum ResponseMode {
 ///
 /// [`EndpointBuilder`] is used to create an [`Endpoint`] instance.
 ///
+/// Each optional setting also has a `maybe_` setter taking an [`Option`],
+/// to set it conditionally without breaking the chain:
+///
+/// ```
+/// use thumbor::{endpoint::ResponseMode, Server};
+///
+/// let server = Server::new_unsafe("http://localhost:8888");
+/// let debug = true;
+///
+/// let endpoint = server.endpoint_builder()
+///     .maybe_response(debug.then_some(ResponseMode::Debug))
+///     .maybe_resize(None::<(i32, i32)>)
+///     .build();
+///
+/// assert_eq!(endpoint.to_path("image.jpg"), "/unsafe/debug/image.jpg");
+/// ```
+///
 /// You can then use the [`Endpoint::to_url`] method to generate the URL,
 /// or the [`Endpoint::to_path`] method to get the path (without the server origin).
 ///
@@ -147,11 +354,24 @@ pub enum ResponseMode {
 /// Thumbor comes pre-packaged with an HTTP loader and a Filesystem loader.
 /// - If you use the HTTP loader, the URI corresponds to the image complete URI.
 /// - If you use the Filesystem loader, the URI corresponds to the path of the image from the images root.
-#[derive(Default, bon::Builder)]
+#[derive(Default, Clone, bon::Builder)]
 #[builder(start_fn = with_server)]
 pub struct Endpoint {
     #[builder(start_fn)]
-    server: Server,
+    pub(crate) server: Server,
+
+    /// Thumbor allows for usage of a filter pipeline that will be applied sequentially to the image.
+    ///
+    /// Use [`EndpointBuilder::filters`] or [`EndpointBuilder::filter`] to append filters to the pipeline.
+    #[builder(field)]
+    filters: Vec<Filter>,
+
+    /// Extra options appended to the URL as a query string, for Thumbor forks that accept them.
+    ///
+    /// Use [`EndpointBuilder::query_param`] to append a parameter.
+    #[builder(field)]
+    query: Vec<(String, String)>,
+
     response: Option<ResponseMode>,
 
     /// Removing surrounding space in images can be done using the trim option.
@@ -163,6 +383,10 @@ pub struct Endpoint {
     /// of the reference pixel and the surrounding pixels is used. If the distance is
     /// within the tolerance they’ll get trimmed. For a RGB image the tolerance would
     /// be within the range 0-442.
+    ///
+    /// Use [`EndpointBuilder::trim_default`] for the common top-left, zero-tolerance trim,
+    /// or pass a [`TrimOrigin`] directly for a trim without tolerance.
+    #[builder(into)]
     trim: Option<Trim>,
 
     /// The manual crop is entirely optional. This is very useful for applications
@@ -175,8 +399,10 @@ pub struct Endpoint {
     /// This crop is performed before the rest of the operations, so it can be used as
     /// a prepare step before resizing and smart-cropping. It is very useful when you
     /// just need to get that celebrity face on a big picture full of people, as an example.
+    ///
+    /// The points can also be fractions of the original image, see [`CropRect::from_percentages`].
     #[builder(into)]
-    crop: Option<Rect>,
+    crop: Option<CropRect>,
 
     /// The fit-in argument specifies that the image should not be auto-cropped
     /// and auto-resized to be **EXACTLY** the specified size, and should be fit in
@@ -229,8 +455,10 @@ pub struct Endpoint {
     ///
     /// **The default value (in case it is omitted) for this option is to use
     /// proportional size (0) to the original image.**
+    ///
+    /// A [`Resize`] without any dimension nor flip is omitted, as if no resize was set.
     #[builder(into)]
-    resize: Option<Point>,
+    resize: Option<Resize>,
 
     /// As was explained above, unless the image is of the same proportion as the desired size,
     /// some cropping will need to occur.
@@ -270,10 +498,6 @@ pub struct Endpoint {
     /// **The default value (in case it is omitted) for this option is [`VAlignment::Middle`].**
     v_align: Option<VAlignment>,
 
-    /// Thumbor allows for usage of a filter pipeline that will be applied sequentially to the image.
-    #[builder(default, into)]
-    filters: Vec<Filter>,
-
     /// Thumbor uses some very advanced techniques for obtaining important points of
     /// the image (referred to as Focal Points in the rest of this documentation).
     ///
@@ -286,9 +510,171 @@ pub struct Endpoint {
     /// **The default value (in case it is omitted) for this option is not to use smart cropping.**
     #[builder(default)]
     smart: bool,
+
+    /// Emits the filters sorted by name, so that endpoints built from filters added in a different
+    /// order produce the same URL (and hit the same cache entries).
+    ///
+    /// Filters with the same name keep their relative order.
+    ///
+    /// **Thumbor applies filters sequentially, so sorting can change the resulting image for
+    /// order-sensitive filters (e.g. `watermark` then `blur`). Only enable it for pipelines
+    /// made of order-insensitive filters.**
+    ///
+    /// **The default value (in case it is omitted) for this option is not to sort filters.**
+    #[builder(default)]
+    sort_filters: bool,
+
+    /// Writes RGB colors as 3-digit shorthand hex when possible (`#fc0` instead of `#ffcc00`),
+    /// to shorten URLs. See [`Color::to_short_hex`].
+    ///
+    /// **The default value (in case it is omitted) for this option is the full 6-digit hex.**
+    #[builder(default)]
+    short_hex_colors: bool,
+
+    /// Keeps only the last occurrence of single-valued filters, such as `max_bytes` and `max_age`,
+    /// where an earlier occurrence would be overridden anyway (see [`Filter::is_single_valued`]).
+    ///
+    /// This is useful when several code paths append the same filter to a pipeline.
+    ///
+    /// **The default value (in case it is omitted) for this option is to keep every filter.**
+    #[builder(default)]
+    dedup_filters: bool,
+
+    /// How the image URI is written in the path, see [`UriEncoding`].
+    ///
+    /// Use [`UriEncoding::Encode`] when image URIs may contain a query string.
+    ///
+    /// **The default value (in case it is omitted) for this option is [`UriEncoding::None`].**
+    #[builder(default)]
+    uri_encoding: UriEncoding,
+
+    /// The length above which [`Endpoint::validate`] warns that the URL is too long.
+    ///
+    /// Some proxies reject URLs longer than about 8KB, which a long filter pipeline can exceed.
+    ///
+    /// **The default value (in case it is omitted) for this option is not to check the length.**
+    max_url_len: Option<usize>,
+
+    /// Truncates the base64url signature to this many characters.
+    ///
+    /// Some setups shorten signatures to save a few bytes per URL. **Vanilla Thumbor rejects
+    /// truncated signatures: the server (or the CDN in front of it) must verify the signature
+    /// with the same truncation.** A shorter signature is also easier to forge. At least one
+    /// character is kept, so that `0` does not leave an empty segment.
+    ///
+    /// [`Server::verify_url`] only accepts them with [`Server::truncated_signatures`].
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// let server = Server::new("http://localhost:8888", "my-security-key").unwrap();
+    /// let endpoint = server.endpoint_builder().signature_len(8).build();
+    ///
+    /// assert_eq!(endpoint.to_path("image.jpg"), "/1HXN_3z4/image.jpg");
+    /// ```
+    ///
+    /// **The default value (in case it is omitted) for this option is the full 28 characters.**
+    signature_len: Option<usize>,
 }
 
 impl<S: State> EndpointBuilder<S> {
+    /// Appends filters to the pipeline.
+    ///
+    /// ```
+    /// use thumbor::{Filter, Server};
+    ///
+    /// let server = Server::new_unsafe("http://localhost:8888");
+    ///
+    /// let endpoint = server.endpoint_builder()
+    ///     .filters([Filter::Brightness(10), Filter::Contrast(20)])
+    ///     .build();
+    /// ```
+    pub fn filters(mut self, filters: impl IntoIterator<Item = Filter>) -> Self {
+        self.filters.extend(filters);
+        self
+    }
+
+    /// Appends the filters to the pipeline, or returns the first error.
+    ///
+    /// This is useful with checked constructors, such as [`Color::named`].
+    ///
+    /// ```
+    /// use thumbor::{filter::Color, Filter, Server};
+    ///
+    /// let server = Server::new_unsafe("http://localhost:8888");
+    /// let fill = |name| Color::named(name).map(|color| Filter::Fill { color, fill_transparent: false });
+    ///
+    /// assert!(server.endpoint_builder().try_filters([fill("blue")]).is_ok());
+    /// assert!(server.endpoint_builder().try_filters([fill("ble")]).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error of the iterator, without appending any filter.
+    pub fn try_filters<E>(
+        self,
+        filters: impl IntoIterator<Item = Result<Filter, E>>,
+    ) -> Result<Self, E> {
+        let filters = filters.into_iter().collect::<Result<Vec<_>, E>>()?;
+        Ok(self.filters(filters))
+    }
+
+    /// Appends a single filter to the pipeline.
+    ///
+    /// ```
+    /// use thumbor::{Filter, Server};
+    ///
+    /// let server = Server::new_unsafe("http://localhost:8888");
+    ///
+    /// let endpoint = server.endpoint_builder()
+    ///     .filter(Filter::Grayscale)
+    ///     .build();
+    /// ```
+    pub fn filter(self, filter: Filter) -> Self {
+        self.filters([filter])
+    }
+
+    /// Appends a [`Filter::Focal`] for each rect, defining several focal regions.
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// let server = Server::new_unsafe("http://localhost:8888");
+    ///
+    /// let endpoint = server.endpoint_builder()
+    ///     .focal_points([(10, 10, 50, 50), (100, 100, 200, 200)].map(Into::into))
+    ///     .build();
+    /// ```
+    pub fn focal_points(self, focal_points: impl IntoIterator<Item = Rect>) -> Self {
+        self.filters(focal_points.into_iter().map(Filter::Focal))
+    }
+
+    /// Appends a `key=value` parameter to the query string of [`Endpoint::to_url`].
+    ///
+    /// **Vanilla Thumbor ignores the query string: this is only meant for forks that read
+    /// extra options from it.** The query string is not part of the signed path, so anyone
+    /// can change it without invalidating the signature. Keys and values are percent-encoded.
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// let server = Server::new_unsafe("http://localhost:8888");
+    ///
+    /// let endpoint = server.endpoint_builder()
+    ///     .query_param("dpr", "2")
+    ///     .query_param("cache", "no store")
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     endpoint.to_url("image.jpg"),
+    ///     "http://localhost:8888/unsafe/image.jpg?dpr=2&cache=no%20store"
+    /// );
+    /// ```
+    pub fn query_param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query.push((key.into(), value.into()));
+        self
+    }
+
     /// The metadata endpoint has **ALL** the options that the image one has,
     /// but instead of actually performing the operations in the image, it just simulates the operations.
     ///
@@ -306,6 +692,24 @@ impl<S: State> EndpointBuilder<S> {
         self.response(ResponseMode::Metadata)
     }
 
+    /// Trims the color of the top-left pixel, without tolerance.
+    ///
+    /// ```
+    /// let server = thumbor::Server::new_unsafe("http://localhost:8888");
+    ///
+    /// let endpoint = server.endpoint_builder()
+    ///     .trim_default()
+    ///     .build();
+    ///
+    /// assert_eq!(endpoint.to_path("image.jpg"), "/unsafe/trim:top-left/image.jpg");
+    /// ```
+    pub fn trim_default(self) -> EndpointBuilder<SetTrim<S>>
+    where
+        S::Trim: IsUnset,
+    {
+        self.trim(Trim::default())
+    }
+
     /// The debug endpoint helps debug focal points by drawing a rectangle around them.
     ///
     /// ```
@@ -321,6 +725,238 @@ impl<S: State> EndpointBuilder<S> {
     {
         self.response(ResponseMode::Debug)
     }
+
+    /// Resizes the image to a square, as `NxN`.
+    ///
+    /// ```
+    /// let server = thumbor::Server::new_unsafe("http://localhost:8888");
+    ///
+    /// let endpoint = server.endpoint_builder().resize_square(200).build();
+    ///
+    /// assert_eq!(endpoint.to_path("image.jpg"), "/unsafe/200x200/image.jpg");
+    /// ```
+    pub fn resize_square(self, size: i32) -> EndpointBuilder<SetResize<S>>
+    where
+        S::Resize: IsUnset,
+    {
+        self.resize(size)
+    }
+
+    /// Resizes the image to a size in CSS pixels, for a display with the given device pixel ratio.
+    ///
+    /// The physical size is rounded to the nearest pixel.
+    ///
+    /// ```
+    /// let server = thumbor::Server::new_unsafe("http://localhost:8888");
+    ///
+    /// let endpoint = server.endpoint_builder()
+    ///     .resize_dpr(150, 100, 2.0)?
+    ///     .build();
+    ///
+    /// assert_eq!(endpoint.to_path("image.jpg"), "/unsafe/300x200/image.jpg");
+    /// # Ok::<(), thumbor::error::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidDpr`] if `dpr` is not a finite positive number, as the size
+    /// would silently become `0x0`, which Thumbor reads as the original size.
+    pub fn resize_dpr(
+        self,
+        css_width: u32,
+        css_height: u32,
+        dpr: f32,
+    ) -> Result<EndpointBuilder<SetResize<S>>, Error>
+    where
+        S::Resize: IsUnset,
+    {
+        if !(dpr.is_finite() && dpr > 0.0) {
+            return Err(Error::InvalidDpr(dpr));
+        }
+
+        // `f64::round` is not available without `std`, and the size is never negative.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let physical = |css: u32| Dimension::Pixels((f64::from(css) * f64::from(dpr) + 0.5) as u32);
+
+        Ok(self.resize(Resize::new(
+            Some(physical(css_width)),
+            Some(physical(css_height)),
+        )))
+    }
+
+    /// Fits the image in the given size, and fills the remaining space with a color,
+    /// so that the image has exactly that size.
+    ///
+    /// This sets [`FitIn::Default`] along with the size, and appends a [`Filter::Fill`].
+    ///
+    /// ```
+    /// let server = thumbor::Server::new_unsafe("http://localhost:8888");
+    ///
+    /// let endpoint = server.endpoint_builder()
+    ///     .fit_and_fill((300, 300), "blue")
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     endpoint.to_path("image.jpg"),
+    ///     "/unsafe/fit-in/300x300/filters:fill(blue)/image.jpg"
+    /// );
+    /// ```
+    pub fn fit_and_fill(
+        self,
+        resize: impl Into<Resize>,
+        color: impl Into<Color>,
+    ) -> EndpointBuilder<SetResize<SetFitIn<S>>>
+    where
+        S::FitIn: IsUnset,
+        S::Resize: IsUnset,
+    {
+        self.fit_in(FitIn::Default)
+            .resize(resize)
+            .filter(Filter::Fill {
+                color: color.into(),
+                fill_transparent: false,
+            })
+    }
+
+    /// Resizes the image, allowing it to be enlarged past its original size.
+    ///
+    /// Thumbor only upscales images that are fit in, so this sets [`FitIn::Default`]
+    /// along with the size, and appends a [`Filter::Upscale`].
+    ///
+    /// ```
+    /// let server = thumbor::Server::new_unsafe("http://localhost:8888");
+    ///
+    /// let endpoint = server.endpoint_builder()
+    ///     .resize_upscale((1920, 1080))
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     endpoint.to_path("image.jpg"),
+    ///     "/unsafe/fit-in/1920x1080/filters:upscale()/image.jpg"
+    /// );
+    /// ```
+    pub fn resize_upscale(
+        self,
+        resize: impl Into<Resize>,
+    ) -> EndpointBuilder<SetResize<SetFitIn<S>>>
+    where
+        S::FitIn: IsUnset,
+        S::Resize: IsUnset,
+    {
+        self.fit_in(FitIn::Default)
+            .resize(resize)
+            .filter(Filter::Upscale)
+    }
+
+    /// Fits the image in the box, swapping its dimensions to match the orientation of the image.
+    ///
+    /// See [`FitIn::adaptive`].
+    ///
+    /// ```
+    /// let server = thumbor::Server::new_unsafe("http://localhost:8888");
+    ///
+    /// let endpoint = server.endpoint_builder().fit_in_adaptive((300, 200)).build();
+    ///
+    /// assert_eq!(endpoint.to_path("image.jpg"), "/unsafe/adaptive-fit-in/300x200/image.jpg");
+    /// ```
+    pub fn fit_in_adaptive(
+        self,
+        resize: impl Into<Resize>,
+    ) -> EndpointBuilder<SetResize<SetFitIn<S>>>
+    where
+        S::FitIn: IsUnset,
+        S::Resize: IsUnset,
+    {
+        self.fit_in(FitIn::Default.adaptive()).resize(resize)
+    }
+}
+
+impl Endpoint {
+    /// Rewrites each filter of the pipeline in place, keeping their order.
+    ///
+    /// This is useful to enforce a policy on endpoints built elsewhere, without rebuilding them.
+    ///
+    /// ```
+    /// use thumbor::{Filter, Server};
+    ///
+    /// let server = Server::new_unsafe("http://localhost:8888");
+    /// let mut endpoint = server.endpoint_builder().filter(Filter::Quality(95)).build();
+    ///
+    /// endpoint.map_filters(|filter| match filter {
+    ///     Filter::Quality(quality) => Filter::Quality(quality.min(85)),
+    ///     filter => filter,
+    /// });
+    ///
+    /// assert_eq!(endpoint.to_path("image.jpg"), "/unsafe/filters:quality(85)/image.jpg");
+    /// ```
+    pub fn map_filters(&mut self, f: impl FnMut(Filter) -> Filter) {
+        self.filters = core::mem::take(&mut self.filters)
+            .into_iter()
+            .map(f)
+            .collect();
+    }
+
+    /// Moves `format` and `quality` after the visual filters of the pipeline.
+    ///
+    /// Output filters describe how the final image is encoded, so they are best emitted once
+    /// every visual filter has been applied. This is a heuristic: the relative order of the
+    /// visual filters, and of the output filters, is kept as is. It is never applied implicitly.
+    ///
+    /// ```
+    /// use thumbor::{filter::Format, Filter, Server};
+    ///
+    /// let server = Server::new_unsafe("http://localhost:8888");
+    /// let mut endpoint = server.endpoint_builder()
+    ///     .filters([Filter::Format(Format::Webp), Filter::Brightness(10)])
+    ///     .build();
+    ///
+    /// endpoint.normalize_filter_order();
+    ///
+    /// assert_eq!(endpoint.to_path("image.jpg"), "/unsafe/filters:brightness(10):format(webp)/image.jpg");
+    /// ```
+    pub fn normalize_filter_order(&mut self) {
+        // A stable sort, so that filters of the same kind keep their order.
+        self.filters.sort_by_key(|filter| {
+            matches!(
+                filter,
+                Filter::Format(_) | Filter::FormatChain(_) | Filter::Quality(_)
+            )
+        });
+    }
+
+    /// Joins the segments of a path relative to the root of Thumbor's file loader, to be
+    /// used as the image URI.
+    ///
+    /// Leading and trailing slashes of each segment are dropped, and empty segments are
+    /// skipped. The file loader does not decode the path, so keep the default
+    /// [`UriEncoding::None`].
+    ///
+    /// This is not an [`EndpointBuilder`] setter, as endpoints do not hold the image URI: the
+    /// same endpoint is used for many images, each passed to [`Endpoint::to_path`].
+    ///
+    /// ```
+    /// use thumbor::{Endpoint, Server};
+    ///
+    /// let uri = Endpoint::join_filesystem_path(&["/photos/", "2024", "beach.jpg"]);
+    /// assert_eq!(uri, "photos/2024/beach.jpg");
+    ///
+    /// let server = Server::new_unsafe("http://localhost:8888");
+    /// let endpoint = server.endpoint_builder().resize((300, 200)).build();
+    /// assert_eq!(endpoint.to_path(uri), "/unsafe/300x200/photos/2024/beach.jpg");
+    /// ```
+    pub fn join_filesystem_path(segments: &[&str]) -> String {
+        let mut uri = String::new();
+        for segment in segments.iter().map(|segment| segment.trim_matches('/')) {
+            if segment.is_empty() {
+                continue;
+            }
+            if !uri.is_empty() {
+                uri.push('/');
+            }
+            uri.push_str(segment);
+        }
+        uri
+    }
 }
 
 pub fn endpoint_from_operations(builder: EndpointBuilder, operations: Vec<Operation>) -> Endpoint {
@@ -329,19 +965,19 @@ pub fn endpoint_from_operations(builder: EndpointBuilder, operations: Vec<Operat
     for operation in operations {
         match operation {
             Operation::Resize(size) => {
-                endpoint.resize = Some(size);
+                endpoint.resize = Some(size.into());
             }
             Operation::Crop(rect) => {
-                endpoint.crop = Some(rect);
+                endpoint.crop = Some(rect.into());
             }
             Operation::FlipHorizontally => {
                 if let Some(resize) = endpoint.resize.as_mut() {
-                    resize.flip_x();
+                    *resize = resize.flip_horizontally();
                 }
             }
             Operation::FlipVertically => {
                 if let Some(resize) = endpoint.resize.as_mut() {
-                    resize.flip_y();
+                    *resize = resize.flip_vertically();
                 }
             }
         }
@@ -1,30 +1,308 @@
-use super::{Endpoint, Filters, Smart};
-use crate::server::Security;
-use base64ct::{Base64Url, Encoding};
+use super::{
+    signature::{sign, Signature},
+    Endpoint, Filters, Smart, UriEncoding,
+};
+use crate::{error::Error, server::Security};
+use alloc::{
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
 use hmac::Mac;
 
 fn stringify<T: ToString>(a: &Option<T>) -> Option<String> {
     a.as_ref().map(ToString::to_string)
 }
 
+/// The options of an [`Endpoint`], rendered once so that they can be signed for many image URIs.
+///
+/// Built by [`Endpoint::compiled`].
+#[derive(Clone)]
+pub struct CompiledEndpoint {
+    path: String,
+    /// The security of the server, already fed with `path` when using HMAC.
+    security: Security,
+    uri_encoding: UriEncoding,
+    signature_len: Option<usize>,
+    /// The base path of the server, without surrounding slashes.
+    base_path: String,
+}
+
+impl CompiledEndpoint {
+    /// Same as [`Endpoint::to_path`], without rendering the options again.
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// let server = Server::new("http://localhost:8888", "my-security-key").unwrap();
+    /// let endpoint = server.endpoint_builder().resize((300, 200)).build();
+    /// let compiled = endpoint.compiled();
+    ///
+    /// assert_eq!(compiled.path("a.jpg"), endpoint.to_path("a.jpg"));
+    /// ```
+    pub fn path(&self, image_uri: &str) -> String {
+        let mut path = String::new();
+        self.path_into(&mut path, image_uri);
+        path
+    }
+
+    /// Same as [`CompiledEndpoint::path`], appending the path to `buf`.
+    pub fn path_into(&self, buf: &mut String, image_uri: &str) {
+        buf.push('/');
+        self.sign_into(buf, image_uri);
+    }
+
+    /// The signature of the path for this image URI, as written in [`CompiledEndpoint::path`].
+    pub fn signature(&self, image_uri: &str) -> Signature {
+        self.signature_of_encoded(&self.uri_encoding.apply(image_uri))
+    }
+
+    /// Same as [`CompiledEndpoint::signature`], for an image URI that is already encoded.
+    fn signature_of_encoded(&self, image_uri: &str) -> Signature {
+        // The security is already fed with the options.
+        let signature = sign(&self.security, image_uri);
+        match (&self.security, self.signature_len) {
+            (Security::Hmac(_), Some(len)) if len < signature.as_str().len() => {
+                Signature::new(&signature.as_str()[..len])
+            }
+            _ => signature,
+        }
+    }
+
+    fn sign(&self, image_uri: &str) -> String {
+        let mut path = String::new();
+        self.sign_into(&mut path, image_uri);
+        path
+    }
+
+    /// Writes the base path, the signature, the options and the image URI.
+    fn sign_into(&self, buf: &mut String, image_uri: &str) {
+        if !self.base_path.is_empty() {
+            buf.push_str(&self.base_path);
+            buf.push('/');
+        }
+
+        let image_uri = self.uri_encoding.apply(image_uri);
+        if self.security != Security::None {
+            buf.push_str(self.signature_of_encoded(&image_uri).as_str());
+            buf.push('/');
+        }
+
+        buf.push_str(&self.path);
+        buf.push_str(&image_uri);
+    }
+}
+
+impl From<Endpoint> for CompiledEndpoint {
+    fn from(endpoint: Endpoint) -> Self {
+        endpoint.compiled()
+    }
+}
+
 impl Endpoint {
-    fn build_path(&self, image_uri: impl ToString) -> String {
-        let parts = [
-            stringify(&self.response),
-            stringify(&self.trim),
-            stringify(&self.crop),
-            stringify(&self.fit_in),
-            stringify(&self.resize),
-            stringify(&self.h_align),
-            stringify(&self.v_align),
-            stringify(&self.smart.then_some(Smart)),
-            stringify(&Filters::new(&self.filters)),
-            stringify(&Some(image_uri)),
-        ];
+    /// The rendered options of the endpoint, in the order they appear in the path.
+    fn parts(&self) -> [(&'static str, Option<String>); 9] {
+        [
+            ("response", stringify(&self.response)),
+            ("trim", stringify(&self.trim)),
+            ("crop", stringify(&self.crop)),
+            ("fit_in", stringify(&self.fit_in)),
+            (
+                "resize",
+                stringify(&self.resize.filter(|resize| !resize.is_unset())),
+            ),
+            ("h_align", stringify(&self.h_align)),
+            ("v_align", stringify(&self.v_align)),
+            ("smart", stringify(&self.smart.then_some(Smart))),
+            (
+                "filters",
+                stringify(&Filters::new(
+                    &self.filters,
+                    self.sort_filters,
+                    self.dedup_filters,
+                    self.short_hex_colors,
+                )),
+            ),
+        ]
+    }
+
+    /// Lists the options that differ between two endpoints, as they would be rendered in the path.
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// let server = Server::new_unsafe("http://localhost:8888");
+    /// let a = server.endpoint_builder().resize((300, 200)).build();
+    /// let b = server.endpoint_builder().resize((300, 200)).smart(true).build();
+    ///
+    /// assert_eq!(a.diff(&b), ["smart: none != smart"]);
+    /// ```
+    pub fn diff(&self, other: &Endpoint) -> Vec<String> {
+        let origins = (
+            "origin",
+            Some(self.server.origin.clone()),
+            Some(other.server.origin.clone()),
+        );
+
+        core::iter::once(origins)
+            .chain(
+                self.parts()
+                    .into_iter()
+                    .zip(other.parts())
+                    .map(|((name, a), (_, b))| (name, a, b)),
+            )
+            .filter(|(_, a, b)| a != b)
+            .map(|(name, a, b)| {
+                let a = a.as_deref().unwrap_or("none");
+                let b = b.as_deref().unwrap_or("none");
+                format!("{name}: {a} != {b}")
+            })
+            .collect()
+    }
+
+    /// Lists the filters emitted in the path, with their arguments, for display or logging.
+    ///
+    /// The filters are sorted and deduplicated as configured on the endpoint.
+    ///
+    /// ```
+    /// use thumbor::{Filter, Server};
+    ///
+    /// let server = Server::new_unsafe("http://localhost:8888");
+    /// let endpoint = server.endpoint_builder().filter(Filter::Blur { radius: 7, sigma: None }).build();
+    ///
+    /// assert_eq!(endpoint.filter_summary(), [("blur".to_string(), vec!["7".to_string()])]);
+    /// ```
+    pub fn filter_summary(&self) -> Vec<(String, Vec<String>)> {
+        Filters::new(
+            &self.filters,
+            self.sort_filters,
+            self.dedup_filters,
+            self.short_hex_colors,
+        )
+        .map(|filters| {
+            filters
+                .filters
+                .into_iter()
+                .map(|filter| {
+                    (
+                        filter.name().to_string(),
+                        filter.args_with(filters.short_hex),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+    }
+
+    /// Renders and signs the options once, for generating paths for many image URIs.
+    ///
+    /// See [`CompiledEndpoint::path`].
+    pub fn compiled(&self) -> CompiledEndpoint {
+        #[cfg(all(feature = "tracing", debug_assertions))]
+        if self.resize == Some(crate::geometry::Resize::from((0, 0))) {
+            tracing::warn!(
+                "resize of 0x0 keeps the original size, dimensions may be uninitialized"
+            );
+        }
+
+        let path = self
+            .resize_only_path()
+            .unwrap_or_else(|| self.generic_path());
+
+        self.signed_prefix(path)
+    }
+
+    /// Compiles the endpoint into a signer that can be shared across threads.
+    ///
+    /// Cloning the [`Arc`] is cheap, and the compiled endpoint only holds the rendered
+    /// options and the HMAC state already fed with them.
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// let server = Server::new("http://localhost:8888", "my-security-key").unwrap();
+    /// let endpoint = server.endpoint_builder().resize((300, 200)).build();
+    /// let expected = endpoint.to_path("a.jpg");
+    ///
+    /// let shared = endpoint.into_shared();
+    /// let handle = std::thread::spawn({
+    ///     let shared = shared.clone();
+    ///     move || shared.path("a.jpg")
+    /// });
+    ///
+    /// assert_eq!(handle.join().unwrap(), expected);
+    /// ```
+    pub fn into_shared(self) -> Arc<CompiledEndpoint> {
+        Arc::new(self.into())
+    }
+
+    fn signed_prefix(&self, path: String) -> CompiledEndpoint {
+        let security = match &self.server.security {
+            Security::Unsafe => Security::Unsafe,
+            Security::None => Security::None,
+            Security::Hmac(hmac) => {
+                let mut mac = hmac.clone();
+                mac.update(path.as_bytes());
+                Security::Hmac(mac)
+            }
+        };
+
+        CompiledEndpoint {
+            path,
+            security,
+            uri_encoding: self.uri_encoding,
+            signature_len: self.signature_len.map(|len| len.max(1)),
+            base_path: self.server.base_path().to_string(),
+        }
+    }
+
+    /// Renders the options of a resize-only endpoint, by far the most common case,
+    /// without going through the generic segment assembly.
+    pub(crate) fn resize_only_path(&self) -> Option<String> {
+        match self {
+            Endpoint {
+                server: _,
+                filters,
+                response: None,
+                trim: None,
+                crop: None,
+                fit_in: None,
+                resize: Some(resize),
+                h_align: None,
+                v_align: None,
+                smart: false,
+                sort_filters: _,
+                short_hex_colors: _,
+                dedup_filters: _,
+                uri_encoding: _,
+                max_url_len: _,
+                signature_len: _,
+                query: _,
+            } if filters.is_empty() && !resize.is_unset() => Some(format!("{resize}/")),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn generic_path(&self) -> String {
+        self.parts()
+            .into_iter()
+            .filter_map(|(_, part)| part)
+            .map(|part| part + "/")
+            .collect()
+    }
 
-        parts.into_iter().flatten().collect::<Vec<_>>().join("/")
+    /// Same as [`Endpoint::to_path`], always going through the generic segment assembly.
+    ///
+    /// Only meant to check the fast paths of [`Endpoint::to_path`] against.
+    #[cfg(test)]
+    pub(crate) fn to_path_generic(&self, image_uri: impl ToString) -> String {
+        let prefix = self.signed_prefix(self.generic_path());
+        format!("/{}", prefix.sign(&image_uri.to_string()))
     }
 
+    /// Returns the path of the image URL, starting with a `/`.
+    ///
     /// ```
     /// use thumbor::Server;
     ///
@@ -34,21 +312,128 @@ impl Endpoint {
     ///
     /// assert_eq!(path, "/unsafe/path/to/my/image.jpg");
     /// ```
+    ///
+    /// The image URI is signed as opaque text, even when it is itself a Thumbor URL with
+    /// `filters:`. Thumbor reads the outer filters up to the first `)/`, so set
+    /// [`UriEncoding::Encode`] to encode the parentheses of such
+    /// nested URLs, as [`Filter::watermark_nested`](crate::Filter::watermark_nested) does.
+    ///
+    /// It never fails, even with the `deny-unsafe` feature: use [`Endpoint::try_to_path`] to
+    /// refuse unsafe servers.
     pub fn to_path(&self, image_uri: impl ToString) -> String {
-        let path = self.build_path(image_uri);
+        let mut path = String::new();
+        self.to_path_into(&mut path, &image_uri.to_string());
+        path
+    }
 
-        let security = match &self.server.security {
-            Security::Unsafe => "unsafe".to_string(),
-            Security::Hmac(hmac) => {
-                let mut mac = hmac.clone();
-                mac.update(path.as_bytes());
+    /// Same as [`Endpoint::to_path`], appending the path to `buf`.
+    ///
+    /// This allows reusing a single buffer when rendering many paths in a loop.
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// let server = Server::new_unsafe("http://localhost:8888");
+    /// let endpoint = server.endpoint_builder().resize((300, 200)).build();
+    ///
+    /// let mut path = String::new();
+    /// for image_uri in ["a.jpg", "b.jpg"] {
+    ///     path.clear();
+    ///     endpoint.to_path_into(&mut path, image_uri);
+    ///     assert_eq!(path, format!("/unsafe/300x200/{image_uri}"));
+    /// }
+    /// ```
+    pub fn to_path_into(&self, buf: &mut String, image_uri: &str) {
+        #[cfg(feature = "tracing")]
+        let start = buf.len();
+
+        self.compiled().path_into(buf, image_uri);
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            path = &buf[start..],
+            options = ?self
+                .parts()
+                .into_iter()
+                .filter_map(|(name, part)| part.map(|_| name))
+                .collect::<Vec<_>>(),
+            filters = ?self.filters.iter().map(super::Filter::name).collect::<Vec<_>>(),
+            "built thumbor path"
+        );
+    }
+
+    /// Same as [`Endpoint::to_path`], without the leading `/`.
+    ///
+    /// This is useful when joining the path with an origin that already ends with a `/`.
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// let server = Server::new_unsafe("http://localhost:8888");
+    /// let endpoint = server.endpoint_builder().build();
+    /// let path = endpoint.to_path_no_leading_slash("path/to/my/image.jpg");
+    ///
+    /// assert_eq!(path, "unsafe/path/to/my/image.jpg");
+    /// ```
+    pub fn to_path_no_leading_slash(&self, image_uri: impl ToString) -> String {
+        self.compiled().sign(&image_uri.to_string())
+    }
+
+    /// Same as [`Endpoint::to_path`], for many image URIs.
+    ///
+    /// The options are rendered and fed to the HMAC only once,
+    /// which makes it cheaper than calling [`Endpoint::to_path`] for each image URI.
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// let server = Server::new_unsafe("http://localhost:8888");
+    /// let endpoint = server.endpoint_builder().resize((300, 200)).build();
+    /// let paths = endpoint
+    ///     .paths_for(["a.jpg", "b.jpg"].into_iter())
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(paths, ["/unsafe/300x200/a.jpg", "/unsafe/300x200/b.jpg"]);
+    /// ```
+    pub fn paths_for<'a>(
+        &'a self,
+        image_uris: impl Iterator<Item = &'a str> + 'a,
+    ) -> impl Iterator<Item = String> + 'a {
+        let compiled = self.compiled();
+
+        image_uris.map(move |image_uri| compiled.path(image_uri))
+    }
 
-                let signature = mac.finalize().into_bytes();
-                Base64Url::encode_string(&signature)
+    /// Same as [`Endpoint::to_path`], for an image URI that is an [`http::Uri`].
+    ///
+    /// Thumbor's HTTP loader assumes `http` when no scheme is given, so the `http` scheme is omitted,
+    /// while any other scheme is kept. URIs without an authority are treated as relative
+    /// to the images root, as the Filesystem loader expects.
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// let server = Server::new_unsafe("http://localhost:8888");
+    /// let endpoint = server.endpoint_builder().build();
+    /// let uri = "https://example.com/image.jpg".parse().unwrap();
+    ///
+    /// assert_eq!(endpoint.to_path_for_uri(&uri), "/unsafe/https://example.com/image.jpg");
+    /// ```
+    #[cfg(feature = "http")]
+    pub fn to_path_for_uri(&self, image_uri: &http::Uri) -> String {
+        let path_and_query = image_uri
+            .path_and_query()
+            .map_or("", http::uri::PathAndQuery::as_str);
+
+        let image_uri = match (image_uri.scheme(), image_uri.authority()) {
+            (Some(scheme), Some(authority)) if *scheme != http::uri::Scheme::HTTP => {
+                format!("{scheme}://{authority}{path_and_query}")
             }
+            (_, Some(authority)) => format!("{authority}{path_and_query}"),
+            (_, None) => path_and_query.trim_start_matches('/').to_string(),
         };
 
-        format!("/{security}/{path}")
+        self.to_path(image_uri)
     }
 
     /// ```
@@ -60,7 +445,68 @@ impl Endpoint {
     ///
     /// assert_eq!(path, "http://localhost:8888/unsafe/path/to/my/image.jpg");
     /// ```
+    ///
+    /// It never fails, even with the `deny-unsafe` feature: use [`Endpoint::try_to_url`] to
+    /// refuse unsafe servers.
     pub fn to_url(&self, image_uri: impl ToString) -> String {
-        format!("{}{}", self.server.origin, self.to_path(image_uri))
+        format!(
+            "{}{}{}",
+            self.server.origin,
+            self.to_path(image_uri),
+            self.query_string()
+        )
+    }
+
+    /// The query string set with [`super::EndpointBuilder::query_param`], including the leading `?`.
+    pub(crate) fn query_string(&self) -> String {
+        let mut query = String::new();
+        for (i, (key, value)) in self.query.iter().enumerate() {
+            query.push(if i == 0 { '?' } else { '&' });
+            query.push_str(&UriEncoding::Encode.apply(key));
+            query.push('=');
+            query.push_str(&UriEncoding::Encode.apply(value));
+        }
+        query
+    }
+
+    /// Same as [`Endpoint::to_path`], but refuses to build `unsafe` paths
+    /// when the `deny-unsafe` feature is enabled.
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// let server = Server::new("http://localhost:8888", "my-security-key").unwrap();
+    /// let endpoint = server.endpoint_builder().build();
+    ///
+    /// assert!(endpoint.try_to_path("path/to/my/image.jpg").is_ok());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnsafeNotAllowed`] if the server is unsafe and the `deny-unsafe` feature is enabled.
+    pub fn try_to_path(&self, image_uri: impl ToString) -> Result<String, Error> {
+        self.check_security()?;
+        Ok(self.to_path(image_uri))
+    }
+
+    /// Same as [`Endpoint::to_url`], but refuses to build `unsafe` URLs
+    /// when the `deny-unsafe` feature is enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnsafeNotAllowed`] if the server is unsafe and the `deny-unsafe` feature is enabled.
+    pub fn try_to_url(&self, image_uri: impl ToString) -> Result<String, Error> {
+        self.check_security()?;
+        Ok(self.to_url(image_uri))
+    }
+
+    #[allow(clippy::unnecessary_wraps, clippy::unused_self)]
+    fn check_security(&self) -> Result<(), Error> {
+        #[cfg(feature = "deny-unsafe")]
+        if matches!(self.server.security, Security::Unsafe | Security::None) {
+            return Err(Error::UnsafeNotAllowed);
+        }
+
+        Ok(())
     }
 }
@@ -0,0 +1,44 @@
+use super::{Endpoint, ResponseMode};
+use crate::{error::Error, metadata::Meta};
+
+impl Endpoint {
+    /// Fetches the metadata of an image from the Thumbor server.
+    ///
+    /// The endpoint must be built with [`EndpointBuilder::metadata`](super::EndpointBuilder::metadata).
+    ///
+    /// `reqwest` is used without its default features: enable one of its TLS features
+    /// in your own `Cargo.toml` to reach an `https` server.
+    ///
+    /// ```no_run
+    /// # async fn run() -> Result<(), thumbor::error::Error> {
+    /// let server = thumbor::Server::new_unsafe("http://localhost:8888");
+    /// let endpoint = server.endpoint_builder().metadata().resize((300, 200)).build();
+    ///
+    /// let meta = endpoint
+    ///     .fetch_metadata(&reqwest::Client::new(), "path/to/my/image.jpg")
+    ///     .await?;
+    ///
+    /// println!("{:?}", meta.thumbor.operations);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_metadata(
+        &self,
+        client: &reqwest::Client,
+        image_uri: impl ToString,
+    ) -> Result<Meta, Error> {
+        if !matches!(self.response, Some(ResponseMode::Metadata)) {
+            return Err(Error::NotMetadata);
+        }
+
+        let body = client
+            .get(self.to_url(image_uri))
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
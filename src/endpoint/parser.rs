@@ -0,0 +1,212 @@
+use super::{Endpoint, ResponseMode, Trim};
+use crate::filter::Filter;
+use crate::{
+    error::Error,
+    geometry::{CropRect, Resize},
+    server::{Security, Server},
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::str::FromStr;
+
+/// The remaining path of a Thumbor URL, consumed one option segment at a time.
+struct Segments<'a>(&'a str);
+
+impl Segments<'_> {
+    /// Consumes the next segment if it can be parsed as `T`.
+    ///
+    /// The last segment is always left untouched, as it belongs to the image URI.
+    fn next_parsed<T: FromStr>(&mut self) -> Option<T> {
+        let (head, tail) = self.0.split_once('/')?;
+        let value = head.parse().ok()?;
+        self.0 = tail;
+        Some(value)
+    }
+
+    /// Consumes the filters segment, if any.
+    ///
+    /// Filter arguments can contain `/` (such as watermark URLs), so the segment ends at the first `)/`.
+    /// A segment starting with `filters:` must be a valid pipeline, rather than being read as
+    /// the image URI.
+    fn next_filters(&mut self) -> Result<Vec<Filter>, Error> {
+        if !self.0.starts_with("filters:") {
+            return Ok(Vec::new());
+        }
+
+        let end = self.0.find(")/").ok_or(Error::InvalidUrl(
+            "the filters must be followed by the image URI",
+        ))? + ")".len();
+        let filters = Filter::parse_pipeline(&self.0[..end])?;
+        self.0 = &self.0[end + "/".len()..];
+        Ok(filters)
+    }
+
+    /// Consumes the next segment if it is exactly `expected`.
+    fn next_is(&mut self, expected: &str) -> bool {
+        match self.0.split_once('/') {
+            Some((head, tail)) if head == expected => {
+                self.0 = tail;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Whether `segment` has the shape of a full base64url HMAC-SHA1 signature.
+fn is_signature(segment: &str) -> bool {
+    segment.len() == 28
+        && segment.ends_with('=')
+        && segment[..27]
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+}
+
+pub(super) fn split_origin(url: &str) -> Result<(&str, &str), Error> {
+    if url.starts_with('/') {
+        return Ok(("", url));
+    }
+
+    let scheme_end = url.find("://").ok_or(Error::InvalidUrl("missing scheme"))? + "://".len();
+    let path_start = url[scheme_end..]
+        .find('/')
+        .ok_or(Error::InvalidUrl("missing path"))?
+        + scheme_end;
+
+    Ok(url.split_at(path_start))
+}
+
+impl Endpoint {
+    /// Parses a Thumbor URL back into an [`Endpoint`] and its image URI.
+    ///
+    /// The URL can either be absolute, or a path starting with a `/`.
+    /// The signature is not verified, and the returned endpoint uses an unsafe
+    /// [`Server`] with the origin of the URL.
+    ///
+    /// The first segment is read as the security segment if it is `unsafe` or a full
+    /// signature; otherwise the URL is read as one from [`Server::new_unsigned`]. Use
+    /// [`Server::parse_url`] for URLs under a base path or with truncated signatures.
+    ///
+    /// ```
+    /// use thumbor::Endpoint;
+    ///
+    /// let url = "http://localhost:8888/unsafe/meta/path/to/my/image.jpg";
+    /// let (endpoint, image_uri) = Endpoint::from_url(url).unwrap();
+    ///
+    /// assert_eq!(image_uri, "path/to/my/image.jpg");
+    /// assert_eq!(endpoint.to_url(&image_uri), url);
+    /// ```
+    pub fn from_url(url: &str) -> Result<(Endpoint, String), Error> {
+        let (origin, path) = split_origin(url)?;
+
+        Self::from_origin_and_path(origin, path)
+    }
+
+    /// Same as [`Endpoint::from_url`], for an already parsed [`http::Uri`].
+    ///
+    /// ```
+    /// use thumbor::Endpoint;
+    ///
+    /// let uri = "http://localhost:8888/unsafe/300x200/path/to/my/image.jpg".parse().unwrap();
+    /// let (endpoint, image_uri) = Endpoint::from_uri(&uri).unwrap();
+    ///
+    /// assert_eq!(image_uri, "path/to/my/image.jpg");
+    /// assert_eq!(endpoint.to_url(&image_uri), uri.to_string());
+    /// ```
+    #[cfg(feature = "http")]
+    pub fn from_uri(uri: &http::Uri) -> Result<(Endpoint, String), Error> {
+        let origin = match (uri.scheme(), uri.authority()) {
+            (Some(scheme), Some(authority)) => format!("{scheme}://{authority}"),
+            _ => String::new(),
+        };
+        let path = uri
+            .path_and_query()
+            .map_or("", http::uri::PathAndQuery::as_str);
+
+        Self::from_origin_and_path(&origin, path)
+    }
+
+    fn from_origin_and_path(origin: &str, path: &str) -> Result<(Endpoint, String), Error> {
+        let path = path
+            .strip_prefix('/')
+            .ok_or(Error::InvalidUrl("the path must start with a `/`"))?;
+
+        match path.split_once('/') {
+            Some((security, rest)) if security == "unsafe" || is_signature(security) => {
+                Self::from_options(Server::new_unsafe(origin), rest)
+            }
+            _ => Self::from_options(Server::new_unsigned(origin), path),
+        }
+    }
+
+    /// Parses the options and the image URI that follow the security segment.
+    fn from_options(server: Server, options: &str) -> Result<(Endpoint, String), Error> {
+        let mut segments = Segments(options);
+
+        let response = segments.next_parsed::<ResponseMode>();
+        if response.is_some() && segments.next_parsed::<ResponseMode>().is_some() {
+            return Err(Error::InvalidUrl("meta and debug are mutually exclusive"));
+        }
+
+        let endpoint = server
+            .endpoint_builder()
+            .maybe_response(response)
+            .maybe_trim(segments.next_parsed::<Trim>())
+            .maybe_crop(segments.next_parsed::<CropRect>())
+            .maybe_fit_in(segments.next_parsed())
+            .maybe_resize(segments.next_parsed::<Resize>())
+            .maybe_h_align(segments.next_parsed())
+            .maybe_v_align(segments.next_parsed())
+            .smart(segments.next_is("smart"))
+            .filters(segments.next_filters()?)
+            .build();
+
+        if segments.0.is_empty() {
+            return Err(Error::InvalidUrl("missing image URI"));
+        }
+
+        Ok((endpoint, segments.0.to_string()))
+    }
+}
+
+impl Server {
+    /// Parses a URL built for this server back into an [`Endpoint`] and its image URI.
+    ///
+    /// Unlike [`Endpoint::from_url`], the base path and the security segment are those of the
+    /// server, and the returned endpoint uses the server itself. The signature is not
+    /// verified, see [`Server::verify_url`].
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// let server = Server::new_unsigned("http://localhost:8888").with_base_path("/images/");
+    /// let url = server.endpoint_builder().resize((300, 200)).build().to_url("a.jpg");
+    ///
+    /// let (endpoint, image_uri) = server.parse_url(&url).unwrap();
+    ///
+    /// assert_eq!(image_uri, "a.jpg");
+    /// assert_eq!(endpoint.to_url(&image_uri), url);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidUrl`] if the URL is not under the base path of the server, and
+    /// the same errors as [`Endpoint::from_url`] otherwise.
+    pub fn parse_url(&self, url: &str) -> Result<(Endpoint, String), Error> {
+        let path = self
+            .path_after_base(url)
+            .ok_or(Error::InvalidUrl("the path is not under the base path"))?;
+
+        let options = if self.security == Security::None {
+            path
+        } else {
+            path.split_once('/')
+                .ok_or(Error::InvalidUrl("missing security segment"))?
+                .1
+        };
+
+        Endpoint::from_options(self.clone(), options)
+    }
+}
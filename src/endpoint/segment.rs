@@ -0,0 +1,74 @@
+use super::{Endpoint, Filter, Filters, FitIn, HAlignment, ResponseMode, Trim, VAlignment};
+use crate::geometry::{CropRect, Resize};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// A segment of the path built by [`Endpoint::to_path`], after the signature.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Segment {
+    Response(ResponseMode),
+    Trim(Trim),
+    Crop(CropRect),
+    FitIn(FitIn),
+    Resize(Resize),
+    HAlign(HAlignment),
+    VAlign(VAlignment),
+    Smart,
+    /// The filters as they are rendered, after sorting and deduplication.
+    Filters(Vec<Filter>),
+    /// The image URI, as it is written in the path.
+    Image(String),
+}
+
+impl Endpoint {
+    /// Lists the segments of the path for this image URI, in order.
+    ///
+    /// Unset options are omitted, as they are from the path.
+    ///
+    /// ```
+    /// use thumbor::{endpoint::Segment, Server};
+    ///
+    /// let server = Server::new_unsafe("http://localhost:8888");
+    /// let endpoint = server.endpoint_builder().resize((300, 200)).smart(true).build();
+    ///
+    /// assert_eq!(
+    ///     endpoint.segments("image.jpg"),
+    ///     [
+    ///         Segment::Resize((300, 200).into()),
+    ///         Segment::Smart,
+    ///         Segment::Image("image.jpg".to_string()),
+    ///     ]
+    /// );
+    /// ```
+    pub fn segments(&self, image_uri: impl ToString) -> Vec<Segment> {
+        let filters = Filters::new(
+            &self.filters,
+            self.sort_filters,
+            self.dedup_filters,
+            self.short_hex_colors,
+        )
+        .map(|filters| filters.filters.into_iter().cloned().collect());
+
+        let segments = [
+            self.response.map(Segment::Response),
+            self.trim.map(Segment::Trim),
+            self.crop.map(Segment::Crop),
+            self.fit_in.map(Segment::FitIn),
+            self.resize
+                .filter(|resize| !resize.is_unset())
+                .map(Segment::Resize),
+            self.h_align.map(Segment::HAlign),
+            self.v_align.map(Segment::VAlign),
+            self.smart.then_some(Segment::Smart),
+            filters.map(Segment::Filters),
+            Some(Segment::Image(
+                self.uri_encoding.apply(&image_uri.to_string()).into_owned(),
+            )),
+        ];
+
+        segments.into_iter().flatten().collect()
+    }
+}
@@ -0,0 +1,194 @@
+use super::{parser::split_origin, Endpoint};
+use crate::server::{Security, Server};
+use alloc::string::{String, ToString};
+use base64ct::{Base64Url, Encoding};
+use core::fmt;
+use hmac::Mac;
+
+/// The signature segment of a path: a base64url HMAC-SHA1 digest, or `unsafe`.
+///
+/// It is empty for servers built with [`Server::new_unsigned`], whose paths have no
+/// signature segment.
+///
+/// Comparing signatures takes the same time wherever they differ, so that a verifying proxy
+/// does not leak how much of a forged signature is right.
+///
+/// ```
+/// use thumbor::Server;
+///
+/// let server = Server::new("http://localhost:8888", "my-security-key").unwrap();
+/// let endpoint = server.endpoint_builder().resize((300, 200)).build();
+///
+/// let signature = endpoint.signature("a.jpg");
+///
+/// assert_eq!(signature.as_str(), "GQTHuhtLqz7lQ3LZdlrHB1dH3oM=");
+/// assert_eq!(endpoint.to_path("a.jpg"), format!("/{signature}/300x200/a.jpg"));
+/// ```
+#[derive(Debug, Clone, Eq)]
+pub struct Signature(String);
+
+impl Signature {
+    pub fn new(signature: impl Into<String>) -> Self {
+        Self(signature.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq for Signature {
+    fn eq(&self, other: &Self) -> bool {
+        let (a, b) = (self.0.as_bytes(), other.0.as_bytes());
+
+        // The length of a signature is not secret.
+        a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Checks `signature` against `path`, accepting signatures truncated to `min_len` characters
+/// or more if set.
+pub(crate) fn verify(
+    security: &Security,
+    signature: &str,
+    path: &str,
+    min_len: Option<usize>,
+) -> bool {
+    match security {
+        Security::Hmac(hmac) if signature.len() >= SIGNATURE_LEN => {
+            let mut mac = hmac.clone();
+            mac.update(path.as_bytes());
+
+            let mut digest = [0; 20];
+            Base64Url::decode(signature, &mut digest)
+                .map_or(false, |digest| mac.verify_slice(digest).is_ok())
+        }
+        // A truncated signature does not decode to whole bytes: compare the characters left.
+        Security::Hmac(_) if min_len.map_or(false, |min_len| signature.len() >= min_len.max(1)) => {
+            sign(security, path)
+                .as_str()
+                .get(..signature.len())
+                .map_or(false, |expected| {
+                    Signature::new(signature) == Signature::new(expected)
+                })
+        }
+        Security::Hmac(_) => false,
+        _ => Signature::new(signature) == sign(security, path),
+    }
+}
+
+/// The length of a base64url HMAC-SHA1 signature, padding included.
+const SIGNATURE_LEN: usize = 28;
+
+/// Signs `path` (the options and the image URI, as written in the URL) with `security`.
+pub(crate) fn sign(security: &Security, path: &str) -> Signature {
+    match security {
+        Security::Unsafe => Signature::new("unsafe"),
+        Security::None => Signature::new(""),
+        Security::Hmac(hmac) => {
+            let mut mac = hmac.clone();
+            mac.update(path.as_bytes());
+
+            let signature = mac.finalize().into_bytes();
+            Signature(Base64Url::encode_string(&signature))
+        }
+    }
+}
+
+impl Endpoint {
+    /// The signature of the path for this image URI, as written in [`Endpoint::to_path`].
+    pub fn signature(&self, image_uri: impl ToString) -> Signature {
+        self.compiled().signature(&image_uri.to_string())
+    }
+}
+
+impl Server {
+    /// Checks the signature of a URL (or a path starting with `/`) built for this server.
+    ///
+    /// The whole path after the signature is checked, query string included. Parameters added
+    /// with [`EndpointBuilder::query_param`](crate::EndpointBuilder::query_param) are not
+    /// signed: remove them first, which is only reliable for image URIs encoded with
+    /// [`UriEncoding::Encode`](crate::endpoint::UriEncoding::Encode).
+    ///
+    /// Signatures truncated with
+    /// [`EndpointBuilder::signature_len`](crate::EndpointBuilder::signature_len) are rejected,
+    /// unless opted into with [`Server::truncated_signatures`].
+    ///
+    /// Servers built with [`Server::new_unsigned`] have no signature to check, so any path
+    /// under their base path is accepted.
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// let server = Server::new("http://localhost:8888", "my-security-key").unwrap();
+    /// let url = server.endpoint_builder().resize((300, 200)).build().to_url("a.jpg");
+    ///
+    /// assert!(server.verify_url(&url));
+    /// assert!(!server.verify_url(&url.replace("300x200", "600x400")));
+    /// ```
+    pub fn verify_url(&self, url: &str) -> bool {
+        let path = match self.path_after_base(url) {
+            Some(path) => path,
+            None => return false,
+        };
+
+        if self.security == Security::None {
+            return true;
+        }
+
+        match path.split_once('/') {
+            Some((signature, signed)) => {
+                verify(&self.security, signature, signed, self.min_signature_len)
+            }
+            None => false,
+        }
+    }
+
+    /// Same as [`Server::verify_url`], accepting a URL signed with any of the keys.
+    ///
+    /// Meant for key rotations, where URLs signed with the old key are still in use. The key
+    /// of the server itself is not tried, unless it is one of `keys`.
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// let old = Server::new("http://localhost:8888", "old-key").unwrap();
+    /// let url = old.endpoint_builder().resize((300, 200)).build().to_url("a.jpg");
+    ///
+    /// let server = Server::new("http://localhost:8888", "new-key").unwrap();
+    ///
+    /// assert!(!server.verify_url(&url));
+    /// assert!(server.verify_with_keys(&url, &["new-key", "old-key"]));
+    /// ```
+    pub fn verify_with_keys(&self, url: &str, keys: &[&str]) -> bool {
+        let (signature, signed) = match self
+            .path_after_base(url)
+            .and_then(|path| path.split_once('/'))
+        {
+            Some(split) => split,
+            None => return false,
+        };
+
+        keys.iter()
+            .filter_map(|key| Security::try_from(key.as_bytes()).ok())
+            .any(|security| verify(&security, signature, signed, self.min_signature_len))
+    }
+
+    /// The path of a URL after the base path of the server, without a leading `/`.
+    pub(crate) fn path_after_base<'a>(&self, url: &'a str) -> Option<&'a str> {
+        let path = split_origin(url).ok()?.1.trim_start_matches('/');
+
+        match self.base_path() {
+            "" => Some(path),
+            base_path => path
+                .strip_prefix(base_path)
+                .and_then(|path| path.strip_prefix('/')),
+        }
+    }
+}
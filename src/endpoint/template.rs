@@ -0,0 +1,51 @@
+use super::{CompiledEndpoint, Endpoint};
+use alloc::string::{String, ToString};
+
+/// A reusable set of options, rendered for many image URIs.
+///
+/// This is the recommended way to share options across a codebase: build the endpoint once,
+/// keep the template around (it is cheap to clone), and render each image URI with it.
+///
+/// ```
+/// use thumbor::{endpoint::EndpointTemplate, Server};
+///
+/// let server = Server::new_unsafe("http://localhost:8888");
+/// let thumbnail = EndpointTemplate::from(server.endpoint_builder().resize((150, 150)).build());
+///
+/// assert_eq!(thumbnail.render("a.jpg"), "/unsafe/150x150/a.jpg");
+/// assert_eq!(thumbnail.render("b.jpg"), "/unsafe/150x150/b.jpg");
+/// ```
+///
+/// Rendering is exactly [`Endpoint::to_path`]. When rendering many image URIs in a row,
+/// use [`EndpointTemplate::compiled`] to render the options only once.
+#[derive(Clone)]
+pub struct EndpointTemplate {
+    endpoint: Endpoint,
+}
+
+impl EndpointTemplate {
+    /// Returns the path of the image URL, as [`Endpoint::to_path`].
+    pub fn render(&self, image_uri: impl ToString) -> String {
+        self.endpoint.to_path(image_uri)
+    }
+
+    /// Returns the full image URL, as [`Endpoint::to_url`].
+    pub fn render_url(&self, image_uri: impl ToString) -> String {
+        self.endpoint.to_url(image_uri)
+    }
+
+    /// See [`Endpoint::compiled`].
+    pub fn compiled(&self) -> CompiledEndpoint {
+        self.endpoint.compiled()
+    }
+
+    pub fn endpoint(&self) -> &Endpoint {
+        &self.endpoint
+    }
+}
+
+impl From<Endpoint> for EndpointTemplate {
+    fn from(endpoint: Endpoint) -> Self {
+        Self { endpoint }
+    }
+}
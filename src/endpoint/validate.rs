@@ -0,0 +1,178 @@
+use super::Endpoint;
+use crate::{
+    error::Error,
+    geometry::{CropRect, Point},
+    server::Security,
+    Filter,
+};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A likely mistake in an [`Endpoint`], reported by [`Endpoint::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Warning {
+    /// The URL is longer than the configured maximum length.
+    UrlTooLong { len: usize, max: usize },
+
+    /// Focal points are set without a resize, so Thumbor has nothing to crop around them.
+    FocalWithoutResize,
+
+    /// Smart cropping is enabled along with explicit alignments, which it overrides
+    /// whenever focal points are detected.
+    SmartOverridesAlignment,
+
+    /// The pipeline both blurs and sharpens the image, which usually cancel each other out.
+    ///
+    /// This is only informational, as some pipelines mean it.
+    BlurAndSharpen,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::UrlTooLong { len, max } => write!(
+                f,
+                "the URL is {len} bytes long, above the maximum of {max}; consider splitting the filter pipeline"
+            ),
+            Warning::FocalWithoutResize => write!(
+                f,
+                "focal points only direct the crop made when resizing, but no resize is set"
+            ),
+            Warning::SmartOverridesAlignment => write!(
+                f,
+                "smart cropping is enabled, so the alignments are ignored when focal points are found"
+            ),
+            Warning::BlurAndSharpen => write!(
+                f,
+                "the filters both blur and sharpen the image, which is usually a mistake"
+            ),
+        }
+    }
+}
+
+impl Endpoint {
+    /// The length of the URL returned by [`Endpoint::to_url`], computed without signing it.
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// let server = Server::new("http://localhost:8888", "my-security-key").unwrap();
+    /// let endpoint = server.endpoint_builder().resize((300, 200)).build();
+    ///
+    /// assert_eq!(endpoint.estimated_url_len("image.jpg"), endpoint.to_url("image.jpg").len());
+    /// ```
+    pub fn estimated_url_len(&self, image_uri: &str) -> usize {
+        let security_len = match self.server.security {
+            Security::Unsafe => "unsafe".len() + "/".len(),
+            Security::None => 0,
+            // A base64 encoded SHA-1 digest.
+            Security::Hmac(_) => self.signature_len.map_or(28, |len| len.clamp(1, 28)) + "/".len(),
+        };
+        let path_len = self
+            .resize_only_path()
+            .unwrap_or_else(|| self.generic_path())
+            .len();
+
+        let base_path_len = match self.server.base_path() {
+            "" => 0,
+            base_path => base_path.len() + "/".len(),
+        };
+
+        self.server.origin.len()
+            + "/".len()
+            + base_path_len
+            + security_len
+            + path_len
+            + self.uri_encoding.apply(image_uri).len()
+            + self.query_string().len()
+    }
+
+    /// Lists the likely mistakes in the endpoint, for the given image URI.
+    ///
+    /// ```
+    /// use thumbor::{endpoint::Warning, Server};
+    ///
+    /// let server = Server::new_unsafe("http://localhost:8888");
+    /// let endpoint = server.endpoint_builder().max_url_len(40).build();
+    ///
+    /// assert!(endpoint.validate("image.jpg").is_empty());
+    /// assert_eq!(
+    ///     endpoint.validate("path/to/my/image.jpg"),
+    ///     [Warning::UrlTooLong { len: 49, max: 40 }]
+    /// );
+    /// ```
+    pub fn validate(&self, image_uri: &str) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+
+        if let Some(max) = self.max_url_len {
+            let len = self.estimated_url_len(image_uri);
+            if len > max {
+                warnings.push(Warning::UrlTooLong { len, max });
+            }
+        }
+
+        let has_focal = self
+            .filters
+            .iter()
+            .any(|filter| matches!(filter, Filter::Focal(_)));
+        let has_resize = self.resize.map_or(false, |resize| !resize.is_unset());
+        if has_focal && !has_resize {
+            warnings.push(Warning::FocalWithoutResize);
+        }
+
+        if self.smart && (self.h_align.is_some() || self.v_align.is_some()) {
+            warnings.push(Warning::SmartOverridesAlignment);
+        }
+
+        let has_blur = self
+            .filters
+            .iter()
+            .any(|filter| matches!(filter, Filter::Blur { .. }));
+        let has_sharpen = self
+            .filters
+            .iter()
+            .any(|filter| matches!(filter, Filter::Sharpen { .. }));
+        if has_blur && has_sharpen {
+            warnings.push(Warning::BlurAndSharpen);
+        }
+
+        warnings
+    }
+
+    /// Checks that the manual crop lies within an image of the given size, such as the one
+    /// found in its metadata.
+    ///
+    /// Crops in fractions of the image always fit.
+    ///
+    /// ```
+    /// use thumbor::{geometry::Rect, Server};
+    ///
+    /// let server = Server::new_unsafe("http://localhost:8888");
+    /// let endpoint = server.endpoint_builder().crop(Rect::new(10, 10, 300, 200)).build();
+    ///
+    /// assert!(endpoint.validate_against_size((800, 600).into()).is_ok());
+    /// assert!(endpoint.validate_against_size((200, 200).into()).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CropOutOfBounds`] if a corner of the crop is outside of the image.
+    pub fn validate_against_size(&self, size: Point) -> Result<(), Error> {
+        let rect = match self.crop {
+            Some(CropRect::Pixels(rect)) => rect,
+            Some(CropRect::Fractions { .. }) | None => return Ok(()),
+        };
+
+        let within = |value: i32, max: i32| (0..=max).contains(&value);
+        if within(rect.left(), size.x())
+            && within(rect.right(), size.x())
+            && within(rect.top(), size.y())
+            && within(rect.bottom(), size.y())
+        {
+            Ok(())
+        } else {
+            Err(Error::CropOutOfBounds { crop: rect, size })
+        }
+    }
+}
@@ -1,4 +1,125 @@
+use crate::geometry::{Point, Rect};
+use alloc::string::{String, ToString};
 pub use hmac::digest::InvalidLength;
 
 #[derive(thiserror::Error, Debug)]
-pub enum Error {}
+pub enum Error {
+    /// The URL does not follow the Thumbor URL format.
+    #[error("invalid thumbor URL: {0}")]
+    InvalidUrl(&'static str),
+
+    /// A segment of a Thumbor URL could not be parsed.
+    #[error("invalid {kind}: `{value}`")]
+    InvalidSegment { kind: &'static str, value: String },
+
+    /// The endpoint is not a metadata endpoint.
+    #[error("the endpoint does not use the metadata response mode")]
+    NotMetadata,
+
+    /// The metadata returned by Thumbor could not be deserialized.
+    #[error("invalid metadata: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The server is unsafe or unsigned, which the `deny-unsafe` feature forbids.
+    #[error("unsafe URLs are not allowed, sign them with a security key")]
+    UnsafeNotAllowed,
+
+    /// The manual crop does not fit in the original image, see [`Endpoint::validate_against_size`].
+    ///
+    /// [`Endpoint::validate_against_size`]: crate::Endpoint::validate_against_size
+    #[error("the crop {crop} does not fit in an image of {size}")]
+    CropOutOfBounds { crop: Rect, size: Point },
+
+    /// The device pixel ratio is not a finite positive number, see [`EndpointBuilder::resize_dpr`].
+    ///
+    /// [`EndpointBuilder::resize_dpr`]: crate::EndpointBuilder::resize_dpr
+    #[error("the device pixel ratio must be finite and positive, got {0}")]
+    InvalidDpr(f32),
+
+    /// The origin of the server does not use `https`, which [`Server::require_https`] forbids.
+    ///
+    /// [`Server::require_https`]: crate::Server::require_https
+    #[error("the origin must use https: `{0}`")]
+    InsecureOrigin(String),
+
+    /// A filter of the URL could not be parsed.
+    #[error(transparent)]
+    Filter(#[from] FilterError),
+
+    /// The request to Thumbor failed.
+    #[cfg(feature = "reqwest")]
+    #[error("request failed: {0}")]
+    Request(#[from] reqwest::Error),
+}
+
+impl Error {
+    pub(crate) fn invalid_segment(kind: &'static str, value: &str) -> Self {
+        Error::InvalidSegment {
+            kind,
+            value: value.to_string(),
+        }
+    }
+}
+
+/// A filter, or one of its arguments, is invalid.
+///
+/// Unlike [`Error::InvalidSegment`], it is not only returned when parsing URLs, but also when
+/// building filters from values that Thumbor would reject.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FilterError {
+    /// The filter could not be parsed.
+    #[error("invalid filter: `{0}`")]
+    Invalid(String),
+
+    /// An argument of the filter is outside of the range Thumbor accepts.
+    #[error("{argument} must be between {min} and {max}, got {value}")]
+    OutOfRange {
+        argument: &'static str,
+        value: i64,
+        min: i64,
+        max: i64,
+    },
+
+    /// The angle is not a multiple of 90 degrees, see [`Filter::rotate_orthogonal`].
+    ///
+    /// [`Filter::rotate_orthogonal`]: crate::Filter::rotate_orthogonal
+    #[error("{0} degrees is not a multiple of 90 degrees")]
+    NotOrthogonal(u16),
+
+    /// A color of the filter is not a known color name.
+    #[error(transparent)]
+    Color(#[from] ColorParseError),
+}
+
+/// The name is not a CSS color name, see [`Color::named`].
+///
+/// [`Color::named`]: crate::filter::Color::named
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("unknown color name: `{0}`")]
+pub struct ColorParseError(pub String);
+
+/// Maps the error to the closest [`std::io::ErrorKind`], keeping it as the source.
+#[cfg(feature = "std")]
+impl From<Error> for std::io::Error {
+    fn from(error: Error) -> Self {
+        use std::io::ErrorKind;
+
+        let kind = match &error {
+            Error::InvalidUrl(_)
+            | Error::InvalidSegment { .. }
+            | Error::NotMetadata
+            | Error::Filter(_)
+            | Error::CropOutOfBounds { .. }
+            | Error::InvalidDpr(_) => ErrorKind::InvalidInput,
+            Error::Json(_) => ErrorKind::InvalidData,
+            Error::UnsafeNotAllowed | Error::InsecureOrigin(_) => ErrorKind::PermissionDenied,
+            #[cfg(feature = "reqwest")]
+            Error::Request(error) if error.is_timeout() => ErrorKind::TimedOut,
+            #[cfg(feature = "reqwest")]
+            Error::Request(_) => ErrorKind::Other,
+        };
+
+        std::io::Error::new(kind, error)
+    }
+}
@@ -1,9 +1,313 @@
-use crate::geometry::Rect;
-use std::fmt;
+//! The filters of the pipeline, and the types of their arguments.
+//!
+//! `thumbor::endpoint::filter` is a deprecated alias of this module.
+//!
+//! ```
+//! use thumbor::filter::{Color, Filter, Format, Radius};
+//!
+//! let filters = [
+//!     Filter::Format(Format::Webp),
+//!     Filter::RoundCorners { radius: Radius::Circle(10), color: Color::from("white"), transparent: false },
+//! ];
+//!
+//! assert_eq!(filters[1].to_string(), "roundcorners(10,white)");
+//! ```
 
+use crate::{
+    endpoint::{Endpoint, UriEncoding},
+    error::{ColorParseError, Error, FilterError},
+    geometry::Rect,
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::{self, Write};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+mod parser;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Color {
     Rgb(u8, u8, u8),
     Name(String),
+    /// Lets Thumbor pick the color that best matches the image borders.
+    Auto,
+    /// Fills with a blurred version of the image.
+    Blur,
+    /// Fills with transparent pixels.
+    Transparent,
+}
+
+impl From<&str> for Color {
+    fn from(name: &str) -> Self {
+        match name {
+            "auto" => Color::Auto,
+            "blur" => Color::Blur,
+            "transparent" => Color::Transparent,
+            _ => Color::Name(name.to_string()),
+        }
+    }
+}
+
+impl From<String> for Color {
+    fn from(name: String) -> Self {
+        match Color::from(name.as_str()) {
+            Color::Name(_) => Color::Name(name),
+            keyword => keyword,
+        }
+    }
+}
+
+impl From<(u8, u8, u8)> for Color {
+    fn from((r, g, b): (u8, u8, u8)) -> Self {
+        Color::Rgb(r, g, b)
+    }
+}
+
+impl From<[u8; 3]> for Color {
+    fn from([r, g, b]: [u8; 3]) -> Self {
+        Color::Rgb(r, g, b)
+    }
+}
+
+/// The CSS color names, with their RGB value.
+const NAMED_COLORS: &[(&str, (u8, u8, u8))] = &[
+    ("aliceblue", (0xf0, 0xf8, 0xff)),
+    ("antiquewhite", (0xfa, 0xeb, 0xd7)),
+    ("aqua", (0x00, 0xff, 0xff)),
+    ("aquamarine", (0x7f, 0xff, 0xd4)),
+    ("azure", (0xf0, 0xff, 0xff)),
+    ("beige", (0xf5, 0xf5, 0xdc)),
+    ("bisque", (0xff, 0xe4, 0xc4)),
+    ("black", (0x00, 0x00, 0x00)),
+    ("blanchedalmond", (0xff, 0xeb, 0xcd)),
+    ("blue", (0x00, 0x00, 0xff)),
+    ("blueviolet", (0x8a, 0x2b, 0xe2)),
+    ("brown", (0xa5, 0x2a, 0x2a)),
+    ("burlywood", (0xde, 0xb8, 0x87)),
+    ("cadetblue", (0x5f, 0x9e, 0xa0)),
+    ("chartreuse", (0x7f, 0xff, 0x00)),
+    ("chocolate", (0xd2, 0x69, 0x1e)),
+    ("coral", (0xff, 0x7f, 0x50)),
+    ("cornflowerblue", (0x64, 0x95, 0xed)),
+    ("cornsilk", (0xff, 0xf8, 0xdc)),
+    ("crimson", (0xdc, 0x14, 0x3c)),
+    ("cyan", (0x00, 0xff, 0xff)),
+    ("darkblue", (0x00, 0x00, 0x8b)),
+    ("darkcyan", (0x00, 0x8b, 0x8b)),
+    ("darkgoldenrod", (0xb8, 0x86, 0x0b)),
+    ("darkgray", (0xa9, 0xa9, 0xa9)),
+    ("darkgreen", (0x00, 0x64, 0x00)),
+    ("darkgrey", (0xa9, 0xa9, 0xa9)),
+    ("darkkhaki", (0xbd, 0xb7, 0x6b)),
+    ("darkmagenta", (0x8b, 0x00, 0x8b)),
+    ("darkolivegreen", (0x55, 0x6b, 0x2f)),
+    ("darkorange", (0xff, 0x8c, 0x00)),
+    ("darkorchid", (0x99, 0x32, 0xcc)),
+    ("darkred", (0x8b, 0x00, 0x00)),
+    ("darksalmon", (0xe9, 0x96, 0x7a)),
+    ("darkseagreen", (0x8f, 0xbc, 0x8f)),
+    ("darkslateblue", (0x48, 0x3d, 0x8b)),
+    ("darkslategray", (0x2f, 0x4f, 0x4f)),
+    ("darkslategrey", (0x2f, 0x4f, 0x4f)),
+    ("darkturquoise", (0x00, 0xce, 0xd1)),
+    ("darkviolet", (0x94, 0x00, 0xd3)),
+    ("deeppink", (0xff, 0x14, 0x93)),
+    ("deepskyblue", (0x00, 0xbf, 0xff)),
+    ("dimgray", (0x69, 0x69, 0x69)),
+    ("dimgrey", (0x69, 0x69, 0x69)),
+    ("dodgerblue", (0x1e, 0x90, 0xff)),
+    ("firebrick", (0xb2, 0x22, 0x22)),
+    ("floralwhite", (0xff, 0xfa, 0xf0)),
+    ("forestgreen", (0x22, 0x8b, 0x22)),
+    ("fuchsia", (0xff, 0x00, 0xff)),
+    ("gainsboro", (0xdc, 0xdc, 0xdc)),
+    ("ghostwhite", (0xf8, 0xf8, 0xff)),
+    ("gold", (0xff, 0xd7, 0x00)),
+    ("goldenrod", (0xda, 0xa5, 0x20)),
+    ("gray", (0x80, 0x80, 0x80)),
+    ("green", (0x00, 0x80, 0x00)),
+    ("greenyellow", (0xad, 0xff, 0x2f)),
+    ("grey", (0x80, 0x80, 0x80)),
+    ("honeydew", (0xf0, 0xff, 0xf0)),
+    ("hotpink", (0xff, 0x69, 0xb4)),
+    ("indianred", (0xcd, 0x5c, 0x5c)),
+    ("indigo", (0x4b, 0x00, 0x82)),
+    ("ivory", (0xff, 0xff, 0xf0)),
+    ("khaki", (0xf0, 0xe6, 0x8c)),
+    ("lavender", (0xe6, 0xe6, 0xfa)),
+    ("lavenderblush", (0xff, 0xf0, 0xf5)),
+    ("lawngreen", (0x7c, 0xfc, 0x00)),
+    ("lemonchiffon", (0xff, 0xfa, 0xcd)),
+    ("lightblue", (0xad, 0xd8, 0xe6)),
+    ("lightcoral", (0xf0, 0x80, 0x80)),
+    ("lightcyan", (0xe0, 0xff, 0xff)),
+    ("lightgoldenrodyellow", (0xfa, 0xfa, 0xd2)),
+    ("lightgray", (0xd3, 0xd3, 0xd3)),
+    ("lightgreen", (0x90, 0xee, 0x90)),
+    ("lightgrey", (0xd3, 0xd3, 0xd3)),
+    ("lightpink", (0xff, 0xb6, 0xc1)),
+    ("lightsalmon", (0xff, 0xa0, 0x7a)),
+    ("lightseagreen", (0x20, 0xb2, 0xaa)),
+    ("lightskyblue", (0x87, 0xce, 0xfa)),
+    ("lightslategray", (0x77, 0x88, 0x99)),
+    ("lightslategrey", (0x77, 0x88, 0x99)),
+    ("lightsteelblue", (0xb0, 0xc4, 0xde)),
+    ("lightyellow", (0xff, 0xff, 0xe0)),
+    ("lime", (0x00, 0xff, 0x00)),
+    ("limegreen", (0x32, 0xcd, 0x32)),
+    ("linen", (0xfa, 0xf0, 0xe6)),
+    ("magenta", (0xff, 0x00, 0xff)),
+    ("maroon", (0x80, 0x00, 0x00)),
+    ("mediumaquamarine", (0x66, 0xcd, 0xaa)),
+    ("mediumblue", (0x00, 0x00, 0xcd)),
+    ("mediumorchid", (0xba, 0x55, 0xd3)),
+    ("mediumpurple", (0x93, 0x70, 0xdb)),
+    ("mediumseagreen", (0x3c, 0xb3, 0x71)),
+    ("mediumslateblue", (0x7b, 0x68, 0xee)),
+    ("mediumspringgreen", (0x00, 0xfa, 0x9a)),
+    ("mediumturquoise", (0x48, 0xd1, 0xcc)),
+    ("mediumvioletred", (0xc7, 0x15, 0x85)),
+    ("midnightblue", (0x19, 0x19, 0x70)),
+    ("mintcream", (0xf5, 0xff, 0xfa)),
+    ("mistyrose", (0xff, 0xe4, 0xe1)),
+    ("moccasin", (0xff, 0xe4, 0xb5)),
+    ("navajowhite", (0xff, 0xde, 0xad)),
+    ("navy", (0x00, 0x00, 0x80)),
+    ("oldlace", (0xfd, 0xf5, 0xe6)),
+    ("olive", (0x80, 0x80, 0x00)),
+    ("olivedrab", (0x6b, 0x8e, 0x23)),
+    ("orange", (0xff, 0xa5, 0x00)),
+    ("orangered", (0xff, 0x45, 0x00)),
+    ("orchid", (0xda, 0x70, 0xd6)),
+    ("palegoldenrod", (0xee, 0xe8, 0xaa)),
+    ("palegreen", (0x98, 0xfb, 0x98)),
+    ("paleturquoise", (0xaf, 0xee, 0xee)),
+    ("palevioletred", (0xdb, 0x70, 0x93)),
+    ("papayawhip", (0xff, 0xef, 0xd5)),
+    ("peachpuff", (0xff, 0xda, 0xb9)),
+    ("peru", (0xcd, 0x85, 0x3f)),
+    ("pink", (0xff, 0xc0, 0xcb)),
+    ("plum", (0xdd, 0xa0, 0xdd)),
+    ("powderblue", (0xb0, 0xe0, 0xe6)),
+    ("purple", (0x80, 0x00, 0x80)),
+    ("rebeccapurple", (0x66, 0x33, 0x99)),
+    ("red", (0xff, 0x00, 0x00)),
+    ("rosybrown", (0xbc, 0x8f, 0x8f)),
+    ("royalblue", (0x41, 0x69, 0xe1)),
+    ("saddlebrown", (0x8b, 0x45, 0x13)),
+    ("salmon", (0xfa, 0x80, 0x72)),
+    ("sandybrown", (0xf4, 0xa4, 0x60)),
+    ("seagreen", (0x2e, 0x8b, 0x57)),
+    ("seashell", (0xff, 0xf5, 0xee)),
+    ("sienna", (0xa0, 0x52, 0x2d)),
+    ("silver", (0xc0, 0xc0, 0xc0)),
+    ("skyblue", (0x87, 0xce, 0xeb)),
+    ("slateblue", (0x6a, 0x5a, 0xcd)),
+    ("slategray", (0x70, 0x80, 0x90)),
+    ("slategrey", (0x70, 0x80, 0x90)),
+    ("snow", (0xff, 0xfa, 0xfa)),
+    ("springgreen", (0x00, 0xff, 0x7f)),
+    ("steelblue", (0x46, 0x82, 0xb4)),
+    ("tan", (0xd2, 0xb4, 0x8c)),
+    ("teal", (0x00, 0x80, 0x80)),
+    ("thistle", (0xd8, 0xbf, 0xd8)),
+    ("tomato", (0xff, 0x63, 0x47)),
+    ("turquoise", (0x40, 0xe0, 0xd0)),
+    ("violet", (0xee, 0x82, 0xee)),
+    ("wheat", (0xf5, 0xde, 0xb3)),
+    ("white", (0xff, 0xff, 0xff)),
+    ("whitesmoke", (0xf5, 0xf5, 0xf5)),
+    ("yellow", (0xff, 0xff, 0x00)),
+    ("yellowgreen", (0x9a, 0xcd, 0x32)),
+];
+
+impl Color {
+    /// Creates a [`Color::Name`], checking that the name is a CSS color name.
+    ///
+    /// The keywords `auto`, `blur` and `transparent` are also accepted. Use [`Color::Name`]
+    /// directly for colors outside of the CSS set, such as custom palettes of Thumbor forks.
+    ///
+    /// ```
+    /// use thumbor::filter::Color;
+    ///
+    /// assert_eq!(Color::named("blue").ok(), Some(Color::Name("blue".to_string())));
+    /// assert!(Color::named("ble").is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ColorParseError`] if the name is not a CSS color name nor a keyword.
+    pub fn named(name: &str) -> Result<Self, ColorParseError> {
+        match Color::from(name) {
+            Color::Name(_) if Color::rgb_of(name).is_none() => {
+                Err(ColorParseError(name.to_string()))
+            }
+            color => Ok(color),
+        }
+    }
+
+    fn rgb_of(name: &str) -> Option<(u8, u8, u8)> {
+        NAMED_COLORS
+            .iter()
+            .find(|(named, _)| named.eq_ignore_ascii_case(name))
+            .map(|(_, rgb)| *rgb)
+    }
+
+    fn rgb(&self) -> Option<(u8, u8, u8)> {
+        match self {
+            Color::Rgb(r, g, b) => Some((*r, *g, *b)),
+            Color::Name(name) => Color::rgb_of(name),
+            Color::Auto | Color::Blur | Color::Transparent => None,
+        }
+    }
+
+    /// The 3-digit shorthand of an RGB color whose channels are all duplicated nibbles.
+    ///
+    /// ```
+    /// use thumbor::filter::Color;
+    ///
+    /// assert_eq!(Color::Rgb(0xff, 0xcc, 0x00).to_short_hex().as_deref(), Some("#fc0"));
+    /// assert_eq!(Color::Rgb(0xff, 0xcd, 0x00).to_short_hex(), None);
+    /// ```
+    pub fn to_short_hex(&self) -> Option<String> {
+        match *self {
+            Color::Rgb(r, g, b) if [r, g, b].iter().all(|channel| channel % 0x11 == 0) => {
+                Some(format!("#{:x}{:x}{:x}", r / 0x11, g / 0x11, b / 0x11))
+            }
+            _ => None,
+        }
+    }
+
+    /// Renders the color, as the shorthand hex if `short_hex` is set and possible.
+    fn render(&self, short_hex: bool) -> String {
+        short_hex
+            .then(|| self.to_short_hex())
+            .flatten()
+            .unwrap_or_else(|| self.to_string())
+    }
+
+    /// Compares two colors, resolving the CSS color names to their RGB value.
+    ///
+    /// Unlike `==`, `Color::Name("red")` and `Color::Rgb(255, 0, 0)` are considered equal.
+    ///
+    /// ```
+    /// use thumbor::filter::Color;
+    ///
+    /// assert!(Color::Name("red".to_string()).approx_eq(&Color::Rgb(255, 0, 0)));
+    /// ```
+    pub fn approx_eq(&self, other: &Color) -> bool {
+        match (self.rgb(), other.rgb()) {
+            (Some(a), Some(b)) => a == b,
+            _ => match (self, other) {
+                (Color::Name(a), Color::Name(b)) => a.eq_ignore_ascii_case(b),
+                _ => self == other,
+            },
+        }
+    }
 }
 
 impl fmt::Display for Color {
@@ -11,11 +315,29 @@ impl fmt::Display for Color {
         match self {
             Color::Rgb(r, g, b) => write!(f, "#{r:02x}{g:02x}{b:02x}",),
             Color::Name(name) => write!(f, "{name}"),
+            Color::Auto => write!(f, "auto"),
+            Color::Blur => write!(f, "blur"),
+            Color::Transparent => write!(f, "transparent"),
         }
     }
 }
 
-#[derive(strum::AsRefStr)]
+/// Serialized as its string form, such as `"red"`, `"#ff0000"` or `"auto"`.
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::AsRefStr, strum::EnumString)]
 #[strum(serialize_all = "lowercase")]
 pub enum Format {
     Webp,
@@ -26,11 +348,239 @@ pub enum Format {
     Heic,
 }
 
+impl Format {
+    /// The `Content-Type` of the images Thumbor returns in this format.
+    ///
+    /// ```
+    /// use thumbor::filter::Format;
+    ///
+    /// assert_eq!(Format::Webp.mime_type(), "image/webp");
+    /// ```
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Format::Webp => "image/webp",
+            Format::Jpeg => "image/jpeg",
+            Format::Gif => "image/gif",
+            Format::Png => "image/png",
+            Format::Avif => "image/avif",
+            Format::Heic => "image/heic",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Radius {
     Ellipsis(u32, u32),
     Circle(u32),
 }
 
+/// A right-angle rotation, to be used with [`Filter::rotation`].
+///
+/// The direction of the rotation depends on the engine: the PIL engine rotates counter-clockwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    None,
+    Cw90,
+    Cw180,
+    Cw270,
+}
+
+impl Rotation {
+    pub fn degrees(self) -> u16 {
+        match self {
+            Rotation::None => 0,
+            Rotation::Cw90 => 90,
+            Rotation::Cw180 => 180,
+            Rotation::Cw270 => 270,
+        }
+    }
+}
+
+/// Common 3x3 kernels, ready to be used as a [`Filter::Convolution`].
+///
+/// ```
+/// use thumbor::filter::Convolution;
+///
+/// assert_eq!(Convolution::blur3x3().to_string(), "convolution(1;2;1;2;4;2;1;2;1,3,true)");
+/// ```
+pub struct Convolution;
+
+impl Convolution {
+    fn kernel3x3(matrix_items: [i16; 9], should_normalize: bool) -> Filter {
+        Filter::Convolution {
+            matrix_items: matrix_items.to_vec(),
+            number_of_columns: 3,
+            should_normalize,
+        }
+    }
+
+    /// Gaussian blur, normalized.
+    ///
+    /// ```text
+    /// 1 2 1
+    /// 2 4 2
+    /// 1 2 1
+    /// ```
+    pub fn blur3x3() -> Filter {
+        Self::kernel3x3([1, 2, 1, 2, 4, 2, 1, 2, 1], true)
+    }
+
+    /// Box blur, averaging each pixel with its neighbours.
+    ///
+    /// ```text
+    /// 1 1 1
+    /// 1 1 1
+    /// 1 1 1
+    /// ```
+    pub fn box_blur3x3() -> Filter {
+        Self::kernel3x3([1; 9], true)
+    }
+
+    /// Sharpen, not normalized as the kernel already sums to 1.
+    ///
+    /// ```text
+    ///  0 -1  0
+    /// -1  5 -1
+    ///  0 -1  0
+    /// ```
+    pub fn sharpen3x3() -> Filter {
+        Self::kernel3x3([0, -1, 0, -1, 5, -1, 0, -1, 0], false)
+    }
+
+    /// Edge detection, leaving flat areas black as the kernel sums to 0.
+    ///
+    /// ```text
+    /// -1 -1 -1
+    /// -1  8 -1
+    /// -1 -1 -1
+    /// ```
+    pub fn edge_detect3x3() -> Filter {
+        Self::kernel3x3([-1, -1, -1, -1, 8, -1, -1, -1, -1], false)
+    }
+
+    /// Emboss, lighting the image from the bottom right.
+    ///
+    /// ```text
+    /// -2 -1  0
+    /// -1  1  1
+    ///  0  1  2
+    /// ```
+    pub fn emboss3x3() -> Filter {
+        Self::kernel3x3([-2, -1, 0, -1, 1, 1, 0, 1, 2], false)
+    }
+}
+
+/// A watermark repeated across the image, with a spacing between tiles.
+///
+/// **The spacing arguments are not supported by upstream Thumbor, only by forks
+/// extending the `watermark` filter.** Upstream Thumbor only supports `repeat` positions,
+/// without spacing. The tiled watermark is rendered as a [`Filter::Custom`].
+///
+/// ```
+/// use thumbor::{filter::WatermarkTiled, Filter};
+///
+/// let filter = Filter::from(WatermarkTiled {
+///     image_url: "logo.png".to_string(),
+///     alpha: 50,
+///     w_ratio: Some(20),
+///     h_ratio: None,
+///     x_spacing: 100,
+///     y_spacing: 50,
+/// });
+///
+/// assert_eq!(filter.to_string(), "watermark(logo.png,repeat,repeat,50,20,none,100,50)");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatermarkTiled {
+    pub image_url: String,
+    pub alpha: u8,
+    pub w_ratio: Option<u8>,
+    pub h_ratio: Option<u8>,
+    /// The horizontal space between two tiles, in pixels.
+    pub x_spacing: u32,
+    /// The vertical space between two tiles, in pixels.
+    pub y_spacing: u32,
+}
+
+impl From<WatermarkTiled> for Filter {
+    fn from(watermark: WatermarkTiled) -> Self {
+        let ratio = |ratio: Option<u8>| ratio.map_or("none".to_string(), |ratio| ratio.to_string());
+
+        Filter::Custom {
+            name: "watermark".to_string(),
+            args: vec![
+                // Custom arguments are written as is, so the commas are encoded here.
+                watermark
+                    .image_url
+                    .replace('(', "%28")
+                    .replace(')', "%29")
+                    .replace(',', "%2C"),
+                "repeat".to_string(),
+                "repeat".to_string(),
+                watermark.alpha.to_string(),
+                ratio(watermark.w_ratio),
+                ratio(watermark.h_ratio),
+                watermark.x_spacing.to_string(),
+                watermark.y_spacing.to_string(),
+            ],
+        }
+    }
+}
+
+/// The filters controlling how the output image is delivered, in one place.
+///
+/// ```
+/// use thumbor::filter::{DeliveryOptions, Format};
+///
+/// let delivery = DeliveryOptions {
+///     quality: Some(80),
+///     format: Some(Format::Webp),
+///     max_age: Some(3600),
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(delivery.filters().unwrap().len(), 3);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeliveryOptions {
+    /// See [`Filter::Quality`], from 0 to 100.
+    pub quality: Option<u8>,
+    /// See [`Filter::Format`].
+    pub format: Option<Format>,
+    /// See [`Filter::MaxBytes`].
+    pub max_bytes: Option<u64>,
+    /// See [`Filter::MaxAge`].
+    pub max_age: Option<u32>,
+}
+
+impl DeliveryOptions {
+    /// The filters to append to the pipeline, in the order `quality`, `format`, `max_bytes`, `max_age`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FilterError::OutOfRange`] if the quality is above 100.
+    pub fn filters(&self) -> Result<Vec<Filter>, FilterError> {
+        if let Some(quality) = self.quality.filter(|quality| *quality > 100) {
+            return Err(FilterError::OutOfRange {
+                argument: "quality",
+                value: quality.into(),
+                min: 0,
+                max: 100,
+            });
+        }
+
+        Ok([
+            self.quality.map(Filter::Quality),
+            self.format.map(Filter::Format),
+            self.max_bytes.map(Filter::MaxBytes),
+            self.max_age.map(Filter::MaxAge),
+        ]
+        .into_iter()
+        .flatten()
+        .collect())
+    }
+}
+
 impl fmt::Display for Radius {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -57,7 +607,7 @@ impl fmt::Display for Radius {
 /// * Then it will try to upscale. This will have no effect, since at this point the image is already $100x100$.
 ///
 /// ```
-/// use thumbor::{Filter, Server, endpoint::filter::Color};
+/// use thumbor::{Filter, Server, filter::Color};
 ///
 /// let server = Server::new_unsafe("http://localhost:8888");
 ///
@@ -78,7 +628,7 @@ impl fmt::Display for Radius {
 ///
 /// assert_eq!(url, "http://localhost:8888/unsafe/filters:blur(7):fill(red)/https%3A%2F%2Fgithub.com%2Fthumbor%2Fthumbor%2Fraw%2Fmaster%2Fexample.jpg");
 /// ```
-#[derive(strum::AsRefStr)]
+#[derive(Debug, Clone, PartialEq, strum::AsRefStr, strum::VariantNames)]
 #[strum(serialize_all = "lowercase")]
 pub enum Filter {
     /// AutoJPG
@@ -535,6 +1085,25 @@ pub enum Filter {
     ///:: http://localhost:8888/unsafe/filters:format(webp)/https%3A%2F%2Fgithub.com%2Fthumbor%2Fthumbor%2Fraw%2Fmaster%2Fexample.jpg
     Format(Format),
 
+    /// # Format chain
+    ///
+    /// A list of output formats, in order of preference, rendered as `format(webp,jpeg)`.
+    ///
+    /// Thumbor itself only accepts a single format: this is for forks that pick the first
+    /// format the client accepts. Use [`Filter::Format`] with upstream Thumbor.
+    ///
+    /// A chain of a single format is parsed back as [`Filter::Format`]: build chains with
+    /// [`Filter::format_chain`], which takes care of it.
+    ///
+    /// ```
+    /// use thumbor::{filter::Format, Filter};
+    ///
+    /// let filter = Filter::FormatChain(vec![Format::Webp, Format::Jpeg]);
+    ///
+    /// assert_eq!(filter.to_string(), "format(webp,jpeg)");
+    /// ```
+    FormatChain(Vec<Format>),
+
     /// Grayscale
     /// =========
     ///
@@ -575,6 +1144,7 @@ pub enum Filter {
     /// ---------
     ///
     /// - ``number-of-bytes`` - The maximum number of bytes for the given image.
+    ///   It is a [`u64`], so byte counts coming from file sizes can be passed without casting.
     ///
     /// Example
     /// -------
@@ -586,7 +1156,31 @@ pub enum Filter {
     /// :: http://localhost:8888/unsafe/filters:max_bytes(7500)/https%3A%2F%2Fgithub.com%2Fthumbor%2Fthumbor%2Fraw%2Fmaster%2Fexample.jpg
     ///
     /// ![Picture after 7500 max_bytes filter](https://thumbor.readthedocs.io/en/latest/_images/tom_after_max_bytes.jpg)
-    MaxBytes(u32),
+    #[strum(serialize = "max_bytes")]
+    MaxBytes(u64),
+
+    /// Max age
+    /// =======
+    ///
+    /// Usage: `max\_age(number-of-seconds)`
+    ///
+    /// Description
+    /// -----------
+    ///
+    /// This filter sets the `Cache-Control: max-age` header of the response,
+    /// overriding the `MAX_AGE` setting of the server for this image.
+    ///
+    /// Arguments
+    /// ---------
+    ///
+    /// - ``number-of-seconds`` - The number of seconds the image can be cached for.
+    ///
+    /// Example
+    /// -------
+    ///
+    /// :: http://localhost:8888/unsafe/filters:max_age(3600)/https%3A%2F%2Fgithub.com%2Fthumbor%2Fthumbor%2Fraw%2Fmaster%2Fexample.jpg
+    #[strum(serialize = "max_age")]
+    MaxAge(u32),
 
     /// No upscale
     /// ==========
@@ -1066,6 +1660,7 @@ pub enum Filter {
     ///
     ///   ![Picture explaining watermark resizing feature](https://thumbor.readthedocs.io/en/latest/_images/tom_watermark_resized_width_height.jpg)
     Watermark {
+        /// Commas are percent-encoded (`%2C`) when rendered, as they would split the arguments.
         image_url: String,
         x: i32,
         y: i32,
@@ -1079,90 +1674,386 @@ pub enum Filter {
 }
 
 impl Filter {
-    fn args(&self) -> Vec<String> {
+    /// Creates a [`Filter::Rotate`] from a right-angle [`Rotation`].
+    ///
+    /// ```
+    /// use thumbor::{Filter, filter::Rotation};
+    ///
+    /// assert_eq!(Filter::rotation(Rotation::Cw90).to_string(), "rotate(90)");
+    /// ```
+    pub fn rotation(rotation: Rotation) -> Self {
+        Filter::Rotate(rotation.degrees())
+    }
+
+    /// Creates a [`Filter::Rotate`] for engines that only rotate by right angles.
+    ///
+    /// The angle is normalized within `0..360` first, so `450` is accepted as `90`.
+    /// [`Filter::Rotate`] itself accepts any angle.
+    ///
+    /// ```
+    /// use thumbor::Filter;
+    ///
+    /// assert_eq!(Filter::rotate_orthogonal(450).ok(), Some(Filter::Rotate(90)));
+    /// assert!(Filter::rotate_orthogonal(45).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FilterError::NotOrthogonal`] if the angle is not a multiple of 90 degrees.
+    pub fn rotate_orthogonal(degrees: u16) -> Result<Self, FilterError> {
+        let rotation = match degrees % 360 {
+            0 => Rotation::None,
+            90 => Rotation::Cw90,
+            180 => Rotation::Cw180,
+            270 => Rotation::Cw270,
+            _ => return Err(FilterError::NotOrthogonal(degrees)),
+        };
+        Ok(Filter::rotation(rotation))
+    }
+
+    /// An opinionated preset: a blur followed by a light noise, which hides the banding
+    /// that blurring smooth gradients tends to produce.
+    ///
+    /// ```
+    /// use thumbor::Filter;
+    ///
+    /// assert_eq!(
+    ///     Filter::soft_blur(7),
+    ///     [Filter::Blur { radius: 7, sigma: None }, Filter::Noise(3)]
+    /// );
+    /// ```
+    pub fn soft_blur(radius: u8) -> [Filter; 2] {
+        [
+            Filter::Blur {
+                radius,
+                sigma: None,
+            },
+            Filter::Noise(3),
+        ]
+    }
+
+    /// An opinionated default quality for each output format: 82 for JPEG, 80 for WebP,
+    /// and 60 for AVIF and HEIC, whose encoders look as good at lower qualities.
+    ///
+    /// GIF and PNG are lossless, so they get Thumbor's own default of 80, which they ignore.
+    /// These values are only a starting point: push a [`Filter::Quality`] of your own to
+    /// override them.
+    ///
+    /// ```
+    /// use thumbor::{filter::Format, Filter};
+    ///
+    /// assert_eq!(Filter::quality_for(Format::Jpeg), Filter::Quality(82));
+    /// assert_eq!(Filter::quality_for(Format::Avif), Filter::Quality(60));
+    /// ```
+    pub fn quality_for(format: Format) -> Self {
+        Filter::Quality(match format {
+            Format::Jpeg => 82,
+            Format::Webp | Format::Gif | Format::Png => 80,
+            Format::Avif | Format::Heic => 60,
+        })
+    }
+
+    /// The name of the filter, as it appears in URLs.
+    ///
+    /// ```
+    /// use thumbor::Filter;
+    ///
+    /// assert_eq!(Filter::MaxBytes(1000).name(), "max_bytes");
+    /// ```
+    pub fn name(&self) -> &str {
         match self {
-            Filter::AutoJPG
-            | Filter::Cover
-            | Filter::Equalize
-            | Filter::ExtractFocalPoints
-            | Filter::Grayscale
-            | Filter::NoUpscale
-            | Filter::RedEye
-            | Filter::Stretch
-            | Filter::StripEXIF
-            | Filter::StripICC
-            | Filter::Upscale => vec![],
-            Filter::Custom { args, .. } => args.clone(),
-            Filter::BackgroundColor(color) => vec![color.to_string()],
-            Filter::Brightness(brightness) => vec![brightness.to_string()],
-            Filter::Contrast(contrast) => vec![contrast.to_string()],
-            Filter::Focal(focal) => vec![focal.to_string()],
-            Filter::Format(format) => vec![format.as_ref().to_string()],
-            Filter::MaxBytes(n) => vec![n.to_string()],
-            Filter::Noise(noise) => vec![noise.to_string()],
-            Filter::Proportion(proportion) => vec![proportion.to_string()],
-            Filter::Quality(quality) => vec![quality.to_string()],
-            Filter::Rotate(rotate) => vec![rotate.to_string()],
-            Filter::Saturation(saturation) => vec![saturation.to_string()],
-            Filter::Blur { radius, sigma } => {
-                let mut args = vec![radius.to_string()];
-                if let Some(sigma) = sigma {
-                    args.push(sigma.to_string());
+            Filter::Custom { name, .. } => name,
+            Filter::FormatChain(_) => "format",
+            _ => self.as_ref(),
+        }
+    }
+
+    /// Creates a [`Filter::FormatChain`], or a [`Filter::Format`] for a single format.
+    ///
+    /// Returns [`FilterError::Invalid`] for an empty chain, as `format()` is not a valid filter.
+    ///
+    /// ```
+    /// use thumbor::{filter::Format, Filter};
+    ///
+    /// let filter = Filter::format_chain([Format::Webp, Format::Jpeg]).unwrap();
+    /// assert_eq!(filter, Filter::FormatChain(vec![Format::Webp, Format::Jpeg]));
+    ///
+    /// let filter = Filter::format_chain([Format::Webp]).unwrap();
+    /// assert_eq!(filter, Filter::Format(Format::Webp));
+    ///
+    /// assert!(Filter::format_chain([]).is_err());
+    /// ```
+    pub fn format_chain(formats: impl IntoIterator<Item = Format>) -> Result<Self, FilterError> {
+        let mut formats: Vec<_> = formats.into_iter().collect();
+        match formats.len() {
+            0 => Err(FilterError::Invalid("format()".to_string())),
+            1 => Ok(Filter::Format(formats.remove(0))),
+            _ => Ok(Filter::FormatChain(formats)),
+        }
+    }
+
+    /// Creates a [`Filter::Watermark`] from a plain image URL.
+    ///
+    /// Parentheses in the URL are percent-encoded, so that they don't end the filter early.
+    /// Use [`Filter::Watermark`] directly for full control over the arguments.
+    ///
+    /// ```
+    /// use thumbor::Filter;
+    ///
+    /// let filter = Filter::watermark("http://example.com/logo(1).png", 10, 20, 50);
+    ///
+    /// assert_eq!(filter.to_string(), "watermark(http://example.com/logo%281%29.png,10,20,50)");
+    /// ```
+    pub fn watermark(image_url: impl AsRef<str>, x: i32, y: i32, alpha: u8) -> Self {
+        Filter::Watermark {
+            image_url: image_url.as_ref().replace('(', "%28").replace(')', "%29"),
+            x,
+            y,
+            alpha,
+            w_ratio: None,
+            h_ratio: None,
+        }
+    }
+
+    /// Creates a [`Filter::Watermark`] whose image is itself processed by Thumbor.
+    ///
+    /// The inner URL is built with [`Endpoint::to_url`], then percent-encoded so that its
+    /// filters don't end the outer filter early.
+    ///
+    /// `x` and `y` are pixel offsets: positive ones are counted from the left and top edges,
+    /// negative ones from the right and bottom edges. They are at most 65535 pixels either
+    /// way, the largest dimension of the images Thumbor decodes. Positions in percents,
+    /// `center` or `repeat` need [`Filter::Custom`].
+    ///
+    /// ```
+    /// use thumbor::{Filter, Server};
+    ///
+    /// let server = Server::new_unsafe("http://localhost:8888");
+    /// let logo = server.endpoint_builder().filter(Filter::Grayscale).build();
+    /// let filter = Filter::watermark_nested(&logo, "logo.png", 10, 20, 50).unwrap();
+    ///
+    /// assert_eq!(
+    ///     filter.to_string(),
+    ///     "watermark(http://localhost:8888/unsafe/filters:grayscale%28%29/logo.png,10,20,50)"
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidUrl`] if the origin of the inner endpoint is not an absolute
+    /// `http(s)` URL, as Thumbor could not load the watermark otherwise, and
+    /// [`FilterError::OutOfRange`] if `x` or `y` is beyond 65535 pixels or `alpha` is above 100.
+    pub fn watermark_nested(
+        endpoint: &Endpoint,
+        image_uri: impl ToString,
+        x: i32,
+        y: i32,
+        alpha: u8,
+    ) -> Result<Self, Error> {
+        let origin = &endpoint.server.origin;
+        let host = ["http://", "https://"]
+            .iter()
+            .find_map(|scheme| origin.strip_prefix(scheme));
+        if !host.map_or(false, |host| !host.is_empty() && !host.contains('/')) {
+            return Err(Error::InvalidUrl(
+                "the origin of a nested watermark must be an absolute http(s) URL",
+            ));
+        }
+        for (argument, offset) in [("x", x), ("y", y)] {
+            if !(-WATERMARK_MAX_OFFSET..=WATERMARK_MAX_OFFSET).contains(&offset) {
+                return Err(FilterError::OutOfRange {
+                    argument,
+                    value: offset.into(),
+                    min: (-WATERMARK_MAX_OFFSET).into(),
+                    max: WATERMARK_MAX_OFFSET.into(),
                 }
-                args
+                .into());
+            }
+        }
+        if alpha > 100 {
+            return Err(FilterError::OutOfRange {
+                argument: "alpha",
+                value: alpha.into(),
+                min: 0,
+                max: 100,
+            }
+            .into());
+        }
+
+        Ok(Filter::Watermark {
+            image_url: UriEncoding::Encode
+                .apply(&endpoint.to_url(image_uri))
+                .into_owned(),
+            x,
+            y,
+            alpha,
+            w_ratio: None,
+            h_ratio: None,
+        })
+    }
+
+    /// The names of the filters supported by Thumbor, as they appear in URLs.
+    ///
+    /// ```
+    /// use thumbor::Filter;
+    ///
+    /// assert!(Filter::variants().any(|name| name == "brightness"));
+    /// ```
+    pub fn variants() -> impl Iterator<Item = &'static str> {
+        <Self as strum::VariantNames>::VARIANTS
+            .iter()
+            .copied()
+            // `Custom` is not a Thumbor filter, its name is chosen by the user,
+            // and `FormatChain` is rendered as `format`.
+            .filter(|name| *name != "custom" && *name != "formatchain")
+    }
+
+    /// Whether only the last occurrence of the filter matters, such as `max_bytes` and `max_age`.
+    ///
+    /// These are the filters collapsed by [`EndpointBuilder::dedup_filters`](crate::EndpointBuilder::dedup_filters).
+    pub fn is_single_valued(&self) -> bool {
+        matches!(self, Filter::MaxBytes(_) | Filter::MaxAge(_))
+    }
+
+    /// Whether the filter is rendered without arguments, as in `grayscale()`.
+    ///
+    /// ```
+    /// use thumbor::Filter;
+    ///
+    /// assert!(Filter::Grayscale.is_argless());
+    /// assert!(!Filter::Quality(80).is_argless());
+    /// ```
+    pub fn is_argless(&self) -> bool {
+        match self {
+            Filter::Custom { args, .. } => args.is_empty(),
+            filter => ARGLESS_FILTERS.contains(filter),
+        }
+    }
+
+    /// The Thumbor filter without arguments called `name`, such as `grayscale`.
+    pub(crate) fn argless(name: &str) -> Option<Filter> {
+        ARGLESS_FILTERS
+            .into_iter()
+            .find(|filter| filter.name() == name)
+    }
+
+    /// Same as the `Display` implementation, see [`Filter::args_with`].
+    ///
+    /// The arguments are written straight into the formatter, without collecting them first.
+    pub(crate) fn write_with(&self, f: &mut fmt::Formatter<'_>, short_hex: bool) -> fmt::Result {
+        f.write_str(self.name())?;
+        f.write_char('(')?;
+        let mut first = true;
+        self.for_each_arg(short_hex, &mut |arg| {
+            if !core::mem::take(&mut first) {
+                f.write_char(',')?;
+            }
+            write!(f, "{arg}")
+        })?;
+        f.write_char(')')
+    }
+
+    /// The arguments of the filter, as they appear in URLs.
+    ///
+    /// Booleans follow the Thumbor documentation of each filter: `sharpen` and `convolution`
+    /// take `true`/`false`, while `fill` and `round_corner` take an optional trailing `1`.
+    ///
+    /// ```
+    /// use thumbor::Filter;
+    ///
+    /// assert_eq!(Filter::Brightness(10).args(), ["10"]);
+    /// ```
+    pub fn args(&self) -> Vec<String> {
+        self.args_with(false)
+    }
+
+    /// Same as [`Filter::args`], rendering colors as shorthand hex when possible if `short_hex` is set.
+    pub(crate) fn args_with(&self, short_hex: bool) -> Vec<String> {
+        let mut args = Vec::new();
+        // Collecting into a `Vec` never fails.
+        let _ = self.for_each_arg(short_hex, &mut |arg| {
+            args.push(arg.to_string());
+            Ok(())
+        });
+        args
+    }
+
+    /// Calls `visit` with each argument of the filter, in order.
+    fn for_each_arg(
+        &self,
+        short_hex: bool,
+        visit: &mut dyn FnMut(&dyn fmt::Display) -> fmt::Result,
+    ) -> fmt::Result {
+        match self {
+            Filter::Custom { args, .. } => args.iter().try_for_each(|arg| visit(arg)),
+            Filter::BackgroundColor(color) => visit(&color.render(short_hex)),
+            Filter::Brightness(brightness) => visit(brightness),
+            Filter::Contrast(contrast) => visit(contrast),
+            Filter::Focal(focal) => visit(focal),
+            Filter::Format(format) => visit(&format.as_ref()),
+            Filter::FormatChain(formats) => formats
+                .iter()
+                .try_for_each(|format| visit(&format.as_ref())),
+            Filter::MaxBytes(n) => visit(n),
+            Filter::MaxAge(seconds) => visit(seconds),
+            Filter::Noise(noise) => visit(noise),
+            Filter::Proportion(proportion) => visit(proportion),
+            Filter::Quality(quality) => visit(quality),
+            Filter::Rotate(rotate) => visit(rotate),
+            Filter::Saturation(saturation) => visit(saturation),
+            Filter::Blur { radius, sigma } => {
+                visit(radius)?;
+                sigma.as_ref().map_or(Ok(()), |sigma| visit(sigma))
             }
             Filter::Convolution {
                 matrix_items,
                 number_of_columns,
                 should_normalize,
-            } => vec![
-                matrix_items
-                    .iter()
-                    .map(ToString::to_string)
-                    .collect::<Vec<_>>()
-                    .join(";"),
-                number_of_columns.to_string(),
-                should_normalize.to_string(),
-            ],
+            } => {
+                visit(&Joined(matrix_items, ';'))?;
+                visit(number_of_columns)?;
+                visit(should_normalize)
+            }
             Filter::Fill {
                 color,
                 fill_transparent,
             } => {
-                let mut args = vec![color.to_string()];
+                visit(&color.render(short_hex))?;
                 if *fill_transparent {
-                    args.push("1".to_string());
+                    visit(&1)?;
                 }
-                args
+                Ok(())
             }
             Filter::Rgb {
                 r_amount,
                 g_amount,
                 b_amount,
-            } => vec![
-                r_amount.to_string(),
-                g_amount.to_string(),
-                b_amount.to_string(),
-            ],
+            } => {
+                visit(r_amount)?;
+                visit(g_amount)?;
+                visit(b_amount)
+            }
             Filter::RoundCorners {
                 radius,
                 color,
                 transparent,
             } => {
-                let mut args = vec![radius.to_string(), color.to_string()];
+                visit(radius)?;
+                visit(&color.render(short_hex))?;
                 if *transparent {
-                    args.push("1".to_string());
+                    visit(&1)?;
                 }
-                args
+                Ok(())
             }
             Filter::Sharpen {
                 sharpen_amount,
                 sharpen_radius,
                 luminance_only,
-            } => vec![
-                sharpen_amount.to_string(),
-                sharpen_radius.to_string(),
-                luminance_only.to_string(),
-            ],
+            } => {
+                visit(sharpen_amount)?;
+                visit(sharpen_radius)?;
+                visit(luminance_only)
+            }
             Filter::Watermark {
                 image_url,
                 x,
@@ -1171,32 +2062,80 @@ impl Filter {
                 w_ratio,
                 h_ratio,
             } => {
-                let mut args = vec![
-                    image_url.to_string(),
-                    x.to_string(),
-                    y.to_string(),
-                    alpha.to_string(),
-                ];
-                if let Some(w_ratio) = w_ratio {
-                    args.push(w_ratio.to_string());
+                visit(&CommaEncoded(image_url))?;
+                visit(x)?;
+                visit(y)?;
+                visit(alpha)?;
+                match (w_ratio, h_ratio) {
+                    (Some(w_ratio), Some(h_ratio)) => {
+                        visit(w_ratio)?;
+                        visit(h_ratio)
+                    }
+                    (Some(w_ratio), None) => visit(w_ratio),
+                    // The ratios are positional, so the width slot must be filled for the height to be read.
+                    (None, Some(h_ratio)) => {
+                        visit(&"none")?;
+                        visit(h_ratio)
+                    }
+                    (None, None) => Ok(()),
                 }
-                if let Some(h_ratio) = h_ratio {
-                    args.push(h_ratio.to_string());
-                }
-                args
             }
+            // Only the filters of `ARGLESS_FILTERS` are left.
+            _ => Ok(()),
         }
     }
 }
 
-impl fmt::Display for Filter {
-    #[allow(clippy::too_many_lines)]
+/// The largest watermark offset, in pixels, see [`Filter::watermark_nested`].
+const WATERMARK_MAX_OFFSET: i32 = 65_535;
+
+/// The filters without arguments, see [`Filter::is_argless`].
+const ARGLESS_FILTERS: [Filter; 11] = [
+    Filter::AutoJPG,
+    Filter::Cover,
+    Filter::Equalize,
+    Filter::ExtractFocalPoints,
+    Filter::Grayscale,
+    Filter::NoUpscale,
+    Filter::RedEye,
+    Filter::Stretch,
+    Filter::StripEXIF,
+    Filter::StripICC,
+    Filter::Upscale,
+];
+
+/// Displays the items separated by a character, as the matrix of `convolution`.
+struct Joined<'a, T>(&'a [T], char);
+
+impl<T: fmt::Display> fmt::Display for Joined<'_, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let name = match self {
-            Filter::Custom { name, .. } => name,
-            _ => self.as_ref(),
-        };
+        for (i, item) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_char(self.1)?;
+            }
+            write!(f, "{item}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Displays a nested URL with its commas encoded, as they would end the filter argument.
+struct CommaEncoded<'a>(&'a str);
+
+impl fmt::Display for CommaEncoded<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, part) in self.0.split(',').enumerate() {
+            if i > 0 {
+                f.write_str("%2C")?;
+            }
+            f.write_str(part)?;
+        }
+        Ok(())
+    }
+}
 
-        write!(f, "{name}({})", self.args().join(","))
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_with(f, false)
     }
 }
@@ -0,0 +1,309 @@
+use super::{Color, Filter, Radius};
+use crate::error::{Error, FilterError};
+use alloc::{string::ToString, vec::Vec};
+use core::str::FromStr;
+
+/// The arguments of a filter, parsed one at a time.
+struct Args<'a> {
+    filter: &'a str,
+    args: Vec<&'a str>,
+}
+
+impl<'a> Args<'a> {
+    fn invalid(&self) -> Error {
+        Error::invalid_segment("filter", self.filter)
+    }
+
+    /// Checks that there are between `min` and `max` arguments.
+    fn expect(&self, min: usize, max: usize) -> Result<(), Error> {
+        if (min..=max).contains(&self.args.len()) {
+            Ok(())
+        } else {
+            Err(self.invalid())
+        }
+    }
+
+    fn get<T: FromStr>(&self, index: usize) -> Result<T, Error> {
+        self.args
+            .get(index)
+            .and_then(|arg| arg.parse().ok())
+            .ok_or_else(|| self.invalid())
+    }
+
+    /// An optional argument, which can also be explicitly set to `none`.
+    fn get_opt<T: FromStr>(&self, index: usize) -> Result<Option<T>, Error> {
+        match self.args.get(index) {
+            None | Some(&"none") => Ok(None),
+            Some(_) => self.get(index).map(Some),
+        }
+    }
+
+    fn get_bool(&self, index: usize) -> Result<bool, Error> {
+        match self.args.get(index).copied() {
+            Some("true" | "True" | "1") => Ok(true),
+            Some("false" | "False" | "0") | None => Ok(false),
+            Some(_) => Err(self.invalid()),
+        }
+    }
+}
+
+impl FromStr for Radius {
+    type Err = Error;
+
+    /// Parses `radius` or `width|height`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::invalid_segment("radius", s);
+
+        match s.split_once('|') {
+            Some((width, height)) => Ok(Radius::Ellipsis(
+                width.parse().map_err(|_| invalid())?,
+                height.parse().map_err(|_| invalid())?,
+            )),
+            None => s.parse().map(Radius::Circle).map_err(|_| invalid()),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = Error;
+
+    /// Parses a keyword, a hex color with or without `#` (or a 3-digit one with `#`), or a color name.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        if hex.len() == 6 && hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16);
+            if let (Ok(r), Ok(g), Ok(b)) = (channel(0), channel(2), channel(4)) {
+                return Ok(Color::Rgb(r, g, b));
+            }
+        }
+        // The shorthand hex needs the `#`, as `fc0` could also be a color name.
+        if s.len() == 4 && hex.len() == 3 && hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            let channel =
+                |i: usize| u8::from_str_radix(&hex[i..=i], 16).map(|nibble| nibble * 0x11);
+            if let (Ok(r), Ok(g), Ok(b)) = (channel(0), channel(1), channel(2)) {
+                return Ok(Color::Rgb(r, g, b));
+            }
+        }
+
+        if s.is_empty() || !s.bytes().all(|byte| byte.is_ascii_alphanumeric()) {
+            return Err(Error::invalid_segment("color", s));
+        }
+
+        Ok(Color::from(s))
+    }
+}
+
+impl FromStr for Filter {
+    type Err = Error;
+
+    /// Parses a filter as it appears in URLs, such as `blur(7)`.
+    ///
+    /// Unknown filters are parsed as [`Filter::Custom`], as are watermarks whose positions
+    /// are not plain pixels (such as `center` or `repeat`).
+    ///
+    /// ```
+    /// use thumbor::Filter;
+    ///
+    /// assert_eq!("max_bytes(2000)".parse::<Filter>().ok(), Some(Filter::MaxBytes(2000)));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, args) = s
+            .strip_suffix(')')
+            .and_then(|s| s.split_once('('))
+            .filter(|(name, _)| {
+                !name.is_empty()
+                    && name
+                        .bytes()
+                        .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_')
+            })
+            .ok_or_else(|| Error::invalid_segment("filter", s))?;
+
+        let args = Args {
+            filter: s,
+            args: if args.is_empty() {
+                Vec::new()
+            } else {
+                args.split(',').collect()
+            },
+        };
+
+        if let Some(filter) = Filter::argless(name) {
+            args.expect(0, 0)?;
+            return Ok(filter);
+        }
+
+        let filter = match name {
+            "backgroundcolor" => {
+                args.expect(1, 1)?;
+                Filter::BackgroundColor(args.get(0)?)
+            }
+            "blur" => {
+                args.expect(1, 2)?;
+                Filter::Blur {
+                    radius: args.get(0)?,
+                    sigma: args.get_opt(1)?,
+                }
+            }
+            "brightness" => {
+                args.expect(1, 1)?;
+                Filter::Brightness(args.get(0)?)
+            }
+            "contrast" => {
+                args.expect(1, 1)?;
+                Filter::Contrast(args.get(0)?)
+            }
+            "convolution" => {
+                args.expect(3, 3)?;
+                Filter::Convolution {
+                    matrix_items: args.args[0]
+                        .split(';')
+                        .map(str::parse)
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| args.invalid())?,
+                    number_of_columns: args.get(1)?,
+                    should_normalize: args.get_bool(2)?,
+                }
+            }
+            "fill" => {
+                args.expect(1, 2)?;
+                Filter::Fill {
+                    color: args.get(0)?,
+                    fill_transparent: args.get_bool(1)?,
+                }
+            }
+            "focal" => {
+                args.expect(1, 1)?;
+                Filter::Focal(args.get(0)?)
+            }
+            "format" => {
+                args.expect(1, usize::MAX)?;
+                let formats = (0..args.args.len())
+                    .map(|index| args.get(index))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Filter::format_chain(formats)?
+            }
+            "max_bytes" => {
+                args.expect(1, 1)?;
+                Filter::MaxBytes(args.get(0)?)
+            }
+            "max_age" => {
+                args.expect(1, 1)?;
+                Filter::MaxAge(args.get(0)?)
+            }
+            "noise" => {
+                args.expect(1, 1)?;
+                Filter::Noise(args.get(0)?)
+            }
+            "proportion" => {
+                args.expect(1, 1)?;
+                Filter::Proportion(args.get(0)?)
+            }
+            "quality" => {
+                args.expect(1, 1)?;
+                Filter::Quality(args.get(0)?)
+            }
+            "rgb" => {
+                args.expect(3, 3)?;
+                Filter::Rgb {
+                    r_amount: args.get(0)?,
+                    g_amount: args.get(1)?,
+                    b_amount: args.get(2)?,
+                }
+            }
+            "rotate" => {
+                args.expect(1, 1)?;
+                Filter::Rotate(args.get(0)?)
+            }
+            "roundcorners" => {
+                args.expect(2, 3)?;
+                Filter::RoundCorners {
+                    radius: args.get(0)?,
+                    color: args.get(1)?,
+                    transparent: args.get_bool(2)?,
+                }
+            }
+            "saturation" => {
+                args.expect(1, 1)?;
+                Filter::Saturation(args.get(0)?)
+            }
+            "sharpen" => {
+                args.expect(3, 3)?;
+                Filter::Sharpen {
+                    sharpen_amount: args.get(0)?,
+                    sharpen_radius: args.get(1)?,
+                    luminance_only: args.get_bool(2)?,
+                }
+            }
+            "watermark" => {
+                args.expect(4, usize::MAX)?;
+                let positions = (args.get(1), args.get(2));
+                match positions {
+                    (Ok(x), Ok(y)) if args.args.len() <= 6 => Filter::Watermark {
+                        image_url: args.args[0].to_string(),
+                        x,
+                        y,
+                        alpha: args.get(3)?,
+                        w_ratio: args.get_opt(4)?,
+                        h_ratio: args.get_opt(5)?,
+                    },
+                    _ => custom(name, &args.args),
+                }
+            }
+            _ => custom(name, &args.args),
+        };
+
+        Ok(filter)
+    }
+}
+
+fn custom(name: &str, args: &[&str]) -> Filter {
+    Filter::Custom {
+        name: name.to_string(),
+        args: args.iter().map(ToString::to_string).collect(),
+    }
+}
+
+/// Splits a filter pipeline on the `:` that are not within the arguments of a filter.
+pub(crate) fn split_pipeline(pipeline: &str) -> impl Iterator<Item = &str> {
+    let mut depth = 0_usize;
+    pipeline
+        .split(move |c| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            c == ':' && depth == 0
+        })
+        .filter(|filter| !filter.is_empty())
+}
+
+impl Filter {
+    /// Parses a filter pipeline, such as `grayscale():quality(80)`.
+    ///
+    /// The pipeline can be prefixed with `filters:`, as in URLs. Parentheses in the arguments
+    /// of a filter, such as a watermark URL, must be encoded (`%28` and `%29`).
+    ///
+    /// ```
+    /// use thumbor::Filter;
+    ///
+    /// let filters = Filter::parse_pipeline("grayscale():quality(80)").unwrap();
+    ///
+    /// assert_eq!(filters, [Filter::Grayscale, Filter::Quality(80)]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FilterError::Invalid`] for the first filter that cannot be parsed.
+    pub fn parse_pipeline(pipeline: &str) -> Result<Vec<Filter>, FilterError> {
+        let pipeline = pipeline.strip_prefix("filters:").unwrap_or(pipeline);
+
+        split_pipeline(pipeline)
+            .map(|filter| {
+                filter
+                    .parse()
+                    .map_err(|_| FilterError::Invalid(filter.to_string()))
+            })
+            .collect()
+    }
+}
@@ -1,20 +1,71 @@
-use std::fmt;
+use alloc::format;
+use core::{fmt, str::FromStr};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, Eq, Ord, PartialEq, PartialOrd, Deserialize)]
+use crate::error::Error;
+
+/// A point, or a size.
+///
+/// It can be deserialized either from an object (`{"x": 800, "y": 600}` or
+/// `{"width": 800, "height": 600}`), or from a two-element array (`[800, 600]`),
+/// and is serialized as an object (`{"x": 800, "y": 600}`).
+#[derive(Debug, Clone, Copy, Eq, Ord, PartialEq, PartialOrd, Deserialize, Serialize)]
+#[serde(from = "PointRepr")]
 pub struct Point {
-    #[serde(alias = "width")]
     x: i32,
-    #[serde(alias = "height")]
     y: i32,
 }
 
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PointRepr {
+    Object {
+        #[serde(alias = "width")]
+        x: i32,
+        #[serde(alias = "height")]
+        y: i32,
+    },
+    Array([i32; 2]),
+}
+
+impl From<PointRepr> for Point {
+    fn from(repr: PointRepr) -> Self {
+        match repr {
+            PointRepr::Object { x, y } => Self { x, y },
+            PointRepr::Array([x, y]) => Self { x, y },
+        }
+    }
+}
+
 impl Point {
     pub fn new(x: i32, y: i32) -> Self {
         Self { x, y }
     }
 
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+
+    /// The straight-line distance between two points.
+    #[cfg(feature = "std")]
+    pub fn euclidean(&self, other: &Point) -> f64 {
+        let dx = f64::from(self.x) - f64::from(other.x);
+        let dy = f64::from(self.y) - f64::from(other.y);
+        dx.hypot(dy)
+    }
+
+    /// The distance between two points along the axes.
+    pub fn manhattan(&self, other: &Point) -> u64 {
+        let dx = i64::from(self.x) - i64::from(other.x);
+        let dy = i64::from(self.y) - i64::from(other.y);
+        dx.unsigned_abs() + dy.unsigned_abs()
+    }
+
     pub fn flip_x(self) -> Self {
         Self {
             x: -self.x,
@@ -30,7 +81,7 @@ impl Point {
     }
 }
 
-impl std::ops::Add for Point {
+impl core::ops::Add for Point {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
@@ -41,7 +92,7 @@ impl std::ops::Add for Point {
     }
 }
 
-impl std::ops::Sub for Point {
+impl core::ops::Sub for Point {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
@@ -52,7 +103,7 @@ impl std::ops::Sub for Point {
     }
 }
 
-impl std::ops::Div<i32> for Point {
+impl core::ops::Div<i32> for Point {
     type Output = Self;
 
     fn div(self, rhs: i32) -> Self::Output {
@@ -63,7 +114,7 @@ impl std::ops::Div<i32> for Point {
     }
 }
 
-impl std::ops::Mul<i32> for Point {
+impl core::ops::Mul<i32> for Point {
     type Output = Self;
 
     fn mul(self, rhs: i32) -> Self::Output {
@@ -74,7 +125,7 @@ impl std::ops::Mul<i32> for Point {
     }
 }
 
-impl std::ops::Mul<f32> for Point {
+impl core::ops::Mul<f32> for Point {
     type Output = Self;
 
     fn mul(self, rhs: f32) -> Self::Output {
@@ -108,13 +159,213 @@ impl From<i32> for Point {
     }
 }
 
+/// The dimensions of an image, as `width x height`.
+///
+/// ```
+/// use thumbor::geometry::Point;
+///
+/// let image = image::RgbImage::new(800, 600);
+///
+/// assert_eq!(Point::from(&image), Point::new(800, 600));
+/// ```
+#[cfg(feature = "image")]
+impl<I: image::GenericImageView> From<&I> for Point {
+    fn from(image: &I) -> Self {
+        let (width, height) = image.dimensions();
+        Self {
+            x: saturating_i32(width),
+            y: saturating_i32(height),
+        }
+    }
+}
+
+/// Dimensions above `i32::MAX` are clamped to it.
+#[cfg(feature = "image")]
+fn saturating_i32(value: u32) -> i32 {
+    i32::try_from(value).unwrap_or(i32::MAX)
+}
+
 impl fmt::Display for Point {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}x{}", self.x, self.y)
     }
 }
 
-#[derive(Deserialize, Debug)]
+impl FromStr for Point {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (x, y) = s
+            .split_once('x')
+            .ok_or_else(|| Error::invalid_segment("point", s))?;
+
+        match (x.parse(), y.parse()) {
+            (Ok(x), Ok(y)) => Ok(Self { x, y }),
+            _ => Err(Error::invalid_segment("point", s)),
+        }
+    }
+}
+
+/// A single dimension of a [`Resize`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+    /// A size in pixels. $0$ means proportional to the original image.
+    Pixels(u32),
+    /// The dimension of the original image.
+    Orig,
+}
+
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Dimension::Pixels(pixels) => write!(f, "{pixels}"),
+            Dimension::Orig => write!(f, "orig"),
+        }
+    }
+}
+
+impl FromStr for Dimension {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "orig" => Ok(Dimension::Orig),
+            _ if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) => s
+                .parse()
+                .map(Dimension::Pixels)
+                .map_err(|_| Error::invalid_segment("dimension", s)),
+            _ => Err(Error::invalid_segment("dimension", s)),
+        }
+    }
+}
+
+/// The size of the image returned by Thumbor, as in `300x200`, `-300x200`, `origx100` or `x200`.
+///
+/// An omitted dimension is proportional to the original image.
+/// A negative dimension flips the image on that axis.
+///
+/// ```
+/// use thumbor::geometry::{Dimension, Resize};
+///
+/// assert_eq!(Resize::from((-300, 200)).to_string(), "-300x200");
+/// assert_eq!(Resize::new(Some(Dimension::Orig), Some(Dimension::Pixels(100))).to_string(), "origx100");
+/// assert_eq!(Resize::new(None, Some(Dimension::Pixels(200))).to_string(), "x200");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Resize {
+    width: Option<Dimension>,
+    height: Option<Dimension>,
+    flip_horizontally: bool,
+    flip_vertically: bool,
+}
+
+impl Resize {
+    pub fn new(width: Option<Dimension>, height: Option<Dimension>) -> Self {
+        Self {
+            width,
+            height,
+            flip_horizontally: false,
+            flip_vertically: false,
+        }
+    }
+
+    /// Whether no dimension nor flip is set, in which case the resize is omitted from the path.
+    ///
+    /// ```
+    /// use thumbor::geometry::Resize;
+    ///
+    /// assert!(Resize::new(None, None).is_unset());
+    /// assert!(!Resize::new(None, None).flip_horizontally().is_unset());
+    /// ```
+    pub fn is_unset(&self) -> bool {
+        *self == Self::default()
+    }
+
+    #[must_use]
+    pub fn flip_horizontally(self) -> Self {
+        Self {
+            flip_horizontally: !self.flip_horizontally,
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn flip_vertically(self) -> Self {
+        Self {
+            flip_vertically: !self.flip_vertically,
+            ..self
+        }
+    }
+}
+
+impl<T: Into<Point>> From<T> for Resize {
+    fn from(size: T) -> Self {
+        let size = size.into();
+        Self {
+            width: Some(Dimension::Pixels(size.x.unsigned_abs())),
+            height: Some(Dimension::Pixels(size.y.unsigned_abs())),
+            flip_horizontally: size.x < 0,
+            flip_vertically: size.y < 0,
+        }
+    }
+}
+
+impl fmt::Display for Resize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_side(
+            f: &mut fmt::Formatter<'_>,
+            flip: bool,
+            dimension: Option<Dimension>,
+        ) -> fmt::Result {
+            if flip {
+                write!(f, "-")?;
+            }
+            if let Some(dimension) = dimension {
+                write!(f, "{dimension}")?;
+            }
+            Ok(())
+        }
+
+        write_side(f, self.flip_horizontally, self.width)?;
+        write!(f, "x")?;
+        write_side(f, self.flip_vertically, self.height)
+    }
+}
+
+impl FromStr for Resize {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn parse_side(side: &str) -> Result<(bool, Option<Dimension>), Error> {
+            let (flip, dimension) = match side.strip_prefix('-') {
+                Some(dimension) => (true, dimension),
+                None => (false, side),
+            };
+            let dimension = match dimension {
+                "" => None,
+                dimension => Some(dimension.parse()?),
+            };
+            Ok((flip, dimension))
+        }
+
+        let (width, height) = s
+            .split_once('x')
+            .ok_or_else(|| Error::invalid_segment("resize", s))?;
+        let (flip_horizontally, width) =
+            parse_side(width).map_err(|_| Error::invalid_segment("resize", s))?;
+        let (flip_vertically, height) =
+            parse_side(height).map_err(|_| Error::invalid_segment("resize", s))?;
+
+        Ok(Self {
+            width,
+            height,
+            flip_horizontally,
+            flip_vertically,
+        })
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Rect {
     left: i32,
     top: i32,
@@ -140,6 +391,22 @@ impl Rect {
         Self::from(((c.x - rx, c.y - ry), (c.x + rx, c.y + ry)))
     }
 
+    pub fn left(&self) -> i32 {
+        self.left
+    }
+
+    pub fn top(&self) -> i32 {
+        self.top
+    }
+
+    pub fn right(&self) -> i32 {
+        self.right
+    }
+
+    pub fn bottom(&self) -> i32 {
+        self.bottom
+    }
+
     pub fn left_top(&self) -> Point {
         Point::new(self.left, self.top)
     }
@@ -154,13 +421,65 @@ impl Rect {
     }
 
     pub fn width(&self) -> i32 {
-        self.bottom - self.top
+        self.right - self.left
     }
 
     pub fn height(&self) -> i32 {
-        self.right - self.left
+        self.bottom - self.top
+    }
+
+    /// The overlapping area of two rects, if any.
+    ///
+    /// ```
+    /// use thumbor::geometry::Rect;
+    ///
+    /// let a = Rect::new(0, 0, 100, 100);
+    /// let b = Rect::new(50, 50, 150, 150);
+    ///
+    /// assert_eq!(a.intersection(&b), Some(Rect::new(50, 50, 100, 100)));
+    /// ```
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let rect = Rect::new(
+            self.left.max(other.left),
+            self.top.max(other.top),
+            self.right.min(other.right),
+            self.bottom.min(other.bottom),
+        );
+
+        (rect.width() > 0 && rect.height() > 0).then_some(rect)
     }
 
+    /// The smallest rect containing both rects.
+    ///
+    /// ```
+    /// use thumbor::geometry::Rect;
+    ///
+    /// let a = Rect::new(0, 0, 100, 100);
+    /// let b = Rect::new(50, 50, 150, 150);
+    ///
+    /// assert_eq!(a.union(&b), Rect::new(0, 0, 150, 150));
+    /// ```
+    #[must_use]
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect::new(
+            self.left.min(other.left),
+            self.top.min(other.top),
+            self.right.max(other.right),
+            self.bottom.max(other.bottom),
+        )
+    }
+
+    /// Scales the rect around its center.
+    ///
+    /// A negative factor mirrors the corners through the center, which inverts the rect
+    /// (`left > right` and `top > bottom`). Use [`Rect::scale_clamped`] to keep it upright.
+    ///
+    /// ```
+    /// use thumbor::geometry::Rect;
+    ///
+    /// assert_eq!(Rect::new(0, 0, 100, 100).scale(0.5), Rect::new(25, 25, 75, 75));
+    /// assert_eq!(Rect::new(0, 0, 100, 100).scale(-1.0), Rect::new(100, 100, 0, 0));
+    /// ```
     #[must_use]
     pub fn scale(mut self, factor: f32) -> Self {
         let center = self.center();
@@ -172,6 +491,25 @@ impl Rect {
 
         self
     }
+
+    /// Same as [`Rect::scale`], swapping the corners if needed so that the rect is never inverted.
+    ///
+    /// ```
+    /// use thumbor::geometry::Rect;
+    ///
+    /// assert_eq!(Rect::new(0, 0, 100, 100).scale_clamped(-1.0), Rect::new(0, 0, 100, 100));
+    /// ```
+    #[must_use]
+    pub fn scale_clamped(self, factor: f32) -> Self {
+        let rect = self.scale(factor);
+
+        Self {
+            left: rect.left.min(rect.right),
+            top: rect.top.min(rect.bottom),
+            right: rect.left.max(rect.right),
+            bottom: rect.top.max(rect.bottom),
+        }
+    }
 }
 
 impl From<(i32, i32, i32, i32)> for Rect {
@@ -194,8 +532,186 @@ impl<T: Into<Point>> From<(T, T)> for Rect {
     }
 }
 
+/// A sub-image rect, such as the one given to `image::imageops::crop`.
+#[cfg(feature = "image")]
+impl From<image::math::Rect> for Rect {
+    fn from(rect: image::math::Rect) -> Self {
+        Self::new(
+            saturating_i32(rect.x),
+            saturating_i32(rect.y),
+            saturating_i32(rect.x.saturating_add(rect.width)),
+            saturating_i32(rect.y.saturating_add(rect.height)),
+        )
+    }
+}
+
 impl fmt::Display for Rect {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}:{}", self.left_top(), self.right_bottom())
     }
 }
+
+impl FromStr for Rect {
+    type Err = Error;
+
+    /// Parses a rect in the `<left>x<top>:<right>x<bottom>` form.
+    ///
+    /// ```
+    /// use thumbor::geometry::Rect;
+    ///
+    /// assert_eq!("10x20:300x400".parse::<Rect>().unwrap(), Rect::new(10, 20, 300, 400));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (left_top, right_bottom) = s
+            .split_once(':')
+            .ok_or_else(|| Error::invalid_segment("rect", s))?;
+
+        match (left_top.parse::<Point>(), right_bottom.parse::<Point>()) {
+            (Ok(left_top), Ok(right_bottom)) => Ok(Self::from((left_top, right_bottom))),
+            _ => Err(Error::invalid_segment("rect", s)),
+        }
+    }
+}
+
+/// The area of a manual crop, either in pixels or as fractions of the original image.
+///
+/// ```
+/// use thumbor::geometry::{CropRect, Rect};
+///
+/// assert_eq!(CropRect::from(Rect::new(10, 20, 300, 400)).to_string(), "10x20:300x400");
+/// assert_eq!(
+///     CropRect::from_percentages(0.1, 0.2, 0.9, 0.8).unwrap().to_string(),
+///     "0.1x0.2:0.9x0.8"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CropRect {
+    Pixels(Rect),
+    /// Fractions of the width and height of the original image, between `0.0` and `1.0`.
+    Fractions {
+        left: f32,
+        top: f32,
+        right: f32,
+        bottom: f32,
+    },
+}
+
+impl CropRect {
+    /// A crop relative to the size of the original image.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidSegment`] if a fraction is not between `0.0` and `1.0`,
+    /// or if `left` is greater than `right` or `top` is greater than `bottom`.
+    pub fn from_percentages(left: f32, top: f32, right: f32, bottom: f32) -> Result<Self, Error> {
+        let in_range = [left, top, right, bottom]
+            .iter()
+            .all(|fraction| (0.0..=1.0).contains(fraction));
+
+        if in_range && left <= right && top <= bottom {
+            Ok(CropRect::Fractions {
+                left,
+                top,
+                right,
+                bottom,
+            })
+        } else {
+            Err(Error::invalid_segment(
+                "crop",
+                &format!("{left}x{top}:{right}x{bottom}"),
+            ))
+        }
+    }
+}
+
+impl FromStr for CropRect {
+    type Err = Error;
+
+    /// Parses a crop in pixels (`10x20:300x400`) or in fractions (`0.1x0.2:0.9x0.8`).
+    ///
+    /// Fractions are told apart by their decimal point, so all four coordinates must use the
+    /// same form: `0.1x20:300x0.8` is rejected.
+    ///
+    /// ```
+    /// use thumbor::geometry::{CropRect, Rect};
+    ///
+    /// assert_eq!(
+    ///     "10x20:300x400".parse::<CropRect>().unwrap(),
+    ///     CropRect::Pixels(Rect::new(10, 20, 300, 400))
+    /// );
+    /// assert_eq!(
+    ///     "0.1x0.2:0.9x0.8".parse::<CropRect>().unwrap(),
+    ///     CropRect::from_percentages(0.1, 0.2, 0.9, 0.8).unwrap()
+    /// );
+    /// assert!("0.1x20:300x0.8".parse::<CropRect>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::invalid_segment("crop", s);
+
+        let coordinates = s
+            .split_once(':')
+            .and_then(|(left_top, right_bottom)| {
+                Some((left_top.split_once('x')?, right_bottom.split_once('x')?))
+            })
+            .map(|((left, top), (right, bottom))| [left, top, right, bottom])
+            .ok_or_else(invalid)?;
+
+        if coordinates
+            .iter()
+            .all(|coordinate| !coordinate.contains('.'))
+        {
+            return s.parse().map(CropRect::Pixels).map_err(|_| invalid());
+        }
+        if !coordinates
+            .iter()
+            .all(|coordinate| coordinate.contains('.'))
+        {
+            return Err(invalid());
+        }
+
+        match coordinates.map(str::parse::<f32>) {
+            [Ok(left), Ok(top), Ok(right), Ok(bottom)] => {
+                CropRect::from_percentages(left, top, right, bottom)
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl From<Rect> for CropRect {
+    fn from(rect: Rect) -> Self {
+        CropRect::Pixels(rect)
+    }
+}
+
+impl fmt::Display for CropRect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        /// Always writes a decimal point, so that `1.0` is not mistaken for a single pixel.
+        #[allow(clippy::float_cmp)]
+        fn write_fraction(f: &mut fmt::Formatter<'_>, fraction: f32) -> fmt::Result {
+            if fraction % 1.0 == 0.0 {
+                write!(f, "{fraction:.1}")
+            } else {
+                write!(f, "{fraction}")
+            }
+        }
+
+        match *self {
+            CropRect::Pixels(rect) => write!(f, "{rect}"),
+            CropRect::Fractions {
+                left,
+                top,
+                right,
+                bottom,
+            } => {
+                write_fraction(f, left)?;
+                write!(f, "x")?;
+                write_fraction(f, top)?;
+                write!(f, ":")?;
+                write_fraction(f, right)?;
+                write!(f, "x")?;
+                write_fraction(f, bottom)
+            }
+        }
+    }
+}
@@ -1,4 +1,6 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![deny(clippy::unwrap_used)]
+#![cfg_attr(not(test), deny(clippy::expect_used, clippy::panic))]
 #![doc(
     html_logo_url = "https://raw.githubusercontent.com/SteelAlloy/thumbor-rs/main/assets/doc/logo.svg"
 )]
@@ -24,9 +26,23 @@
 //!
 //! let url = endpoint.to_url("path/to/my/image.jpg");
 //! ```
+//!
+//! # Features
+//!
+//! - `std` (default): without it, the crate is `no_std` and only depends on `alloc`.
+//! - `http`: build paths from an `http::Uri` image URI.
+//! - `reqwest`: fetch the metadata of an image.
+//! - `image`: convert the dimensions and rects of the `image` crate into points and rects.
+//! - `tracing`: warn about likely mistakes when building paths, and log each built path
+//!   with its active options and filters at the debug level.
+//! - `deny-unsafe`: make [`Endpoint::try_to_path`] and [`Endpoint::try_to_url`] refuse unsafe
+//!   or unsigned servers.
+
+extern crate alloc;
 
 pub mod endpoint;
 pub mod error;
+pub mod filter;
 pub mod geometry;
 pub mod metadata;
 mod server;
@@ -34,5 +50,21 @@ mod server;
 #[cfg(test)]
 mod tests;
 
-pub use endpoint::{filter::Filter, Endpoint, EndpointBuilder};
+pub use endpoint::{Endpoint, EndpointBuilder};
+pub use filter::Filter;
 pub use server::Server;
+
+/// The two ways of rendering the options, for the benchmarks only. Not part of the API.
+#[doc(hidden)]
+pub mod __bench {
+    use crate::Endpoint;
+    use alloc::string::String;
+
+    pub fn resize_only_path(endpoint: &Endpoint) -> Option<String> {
+        endpoint.resize_only_path()
+    }
+
+    pub fn generic_path(endpoint: &Endpoint) -> String {
+        endpoint.generic_path()
+    }
+}
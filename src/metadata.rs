@@ -1,29 +1,49 @@
-use crate::geometry::{Point, Rect};
-use serde::Deserialize;
+use crate::{
+    endpoint::endpoint_from_operations,
+    geometry::{Point, Rect},
+    Endpoint, EndpointBuilder,
+};
+use alloc::{string::String, vec::Vec};
+use serde::{Deserialize, Serialize, Serializer};
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct Meta {
     pub thumbor: Data,
 }
 
-#[derive(Deserialize, Debug)]
+impl Meta {
+    /// Rebuilds the endpoint performing the same operations as the one that returned the metadata.
+    ///
+    /// Flips are turned into negative resize dimensions.
+    pub fn to_endpoint(&self, builder: EndpointBuilder) -> Endpoint {
+        endpoint_from_operations(builder, self.thumbor.operations.clone())
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
 pub struct Source {
     pub url: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct Data {
     // pub focal_points: Vec<FocalPoint>,
     pub source: Source,
     pub operations: Vec<Operation>,
 }
 
-#[derive(Deserialize, Debug, Default)]
+#[derive(Deserialize, Serialize, Debug, Default)]
 pub struct FocalPoint {
     pub x: i32,
     pub y: i32,
     pub height: i32,
     pub width: i32,
+    /// The weight of the focal point.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub z: Option<f32>,
+    /// What detected the focal point, such as `alignment` or `face`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
 }
 
 impl From<FocalPoint> for Rect {
@@ -32,11 +52,26 @@ impl From<FocalPoint> for Rect {
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Operation {
-    Resize(Point),
+    Resize(#[serde(serialize_with = "serialize_size")] Point),
     Crop(Rect),
     FlipHorizontally,
     FlipVertically,
 }
+
+/// Serializes a size as Thumbor does, with `width` and `height` fields.
+fn serialize_size<S: Serializer>(size: &Point, serializer: S) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct Size {
+        width: i32,
+        height: i32,
+    }
+
+    Size {
+        width: size.x(),
+        height: size.y(),
+    }
+    .serialize(serializer)
+}
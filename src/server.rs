@@ -1,4 +1,6 @@
-use super::{Endpoint, EndpointBuilder};
+use super::{error::Error, Endpoint, EndpointBuilder};
+use alloc::string::String;
+use core::fmt;
 use hmac::{digest::InvalidLength, Hmac, Mac};
 use sha1::Sha1;
 
@@ -8,16 +10,61 @@ pub type HmacSha1 = Hmac<Sha1>;
 pub enum Security {
     #[default]
     Unsafe,
+    /// No security segment at all, as in `/300x200/image.jpg`.
+    ///
+    /// Thumbor itself always expects one: this is only for servers or proxies that serve
+    /// paths without it, such as a CDN rewriting them to `unsafe` paths.
+    None,
     /// hmac is the signature that ensures [security](https://thumbor.readthedocs.io/en/latest/security.html)
     Hmac(HmacSha1),
 }
 
+impl Security {
+    /// A digest of the key, to compare securities without exposing the key.
+    fn fingerprint(hmac: &HmacSha1) -> hmac::digest::CtOutput<HmacSha1> {
+        hmac.clone().chain_update(b"thumbor-fingerprint").finalize()
+    }
+}
+
+impl PartialEq for Security {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Security::Unsafe, Security::Unsafe) | (Security::None, Security::None) => true,
+            (Security::Hmac(a), Security::Hmac(b)) => {
+                Security::fingerprint(a) == Security::fingerprint(b)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Security {}
+
+/// The key is never printed.
+impl fmt::Debug for Security {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Security::Unsafe => write!(f, "Unsafe"),
+            Security::None => write!(f, "None"),
+            Security::Hmac(_) => write!(f, "Hmac(<redacted>)"),
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for Security {
+    type Error = InvalidLength;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let hmac = HmacSha1::new_from_slice(value)?;
+        Ok(Security::Hmac(hmac))
+    }
+}
+
 impl TryFrom<String> for Security {
     type Error = InvalidLength;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        let hmac = HmacSha1::new_from_slice(value.as_bytes())?;
-        Ok(Security::Hmac(hmac))
+        Security::try_from(value.as_bytes())
     }
 }
 
@@ -26,10 +73,18 @@ impl TryFrom<String> for Security {
 ///
 /// let server = Server::new("http://localhost:8888", "my-security-key").unwrap();
 /// ```
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
 pub struct Server {
     pub origin: String,
     pub security: Security,
+    /// The sub-path Thumbor is mounted under, written before the signature.
+    ///
+    /// See [`Server::with_base_path`].
+    pub base_path: String,
+    /// The shortest truncated signature accepted when verifying URLs, if any.
+    ///
+    /// See [`Server::truncated_signatures`].
+    pub min_signature_len: Option<usize>,
 }
 
 impl Server {
@@ -37,6 +92,24 @@ impl Server {
         Ok(Server {
             origin: origin.into(),
             security: key.into().try_into()?,
+            base_path: String::new(),
+            min_signature_len: None,
+        })
+    }
+
+    /// Same as [`Server::new`], for keys that are not valid UTF-8.
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// let server = Server::new_from_bytes("http://localhost:8888", &[0xde, 0xad, 0xbe, 0xef]).unwrap();
+    /// ```
+    pub fn new_from_bytes(origin: impl Into<String>, key: &[u8]) -> Result<Self, InvalidLength> {
+        Ok(Server {
+            origin: origin.into(),
+            security: key.try_into()?,
+            base_path: String::new(),
+            min_signature_len: None,
         })
     }
 
@@ -50,6 +123,105 @@ impl Server {
         Server {
             origin: origin.into(),
             security: Security::Unsafe,
+            base_path: String::new(),
+            min_signature_len: None,
+        }
+    }
+
+    /// Builds paths without any security segment, neither `unsafe` nor a signature.
+    ///
+    /// Thumbor rejects such paths: the server must be one that accepts them, such as a proxy
+    /// that adds the `unsafe` segment back.
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// let server = Server::new_unsigned("http://localhost:8888");
+    /// let endpoint = server.endpoint_builder().resize((300, 200)).build();
+    ///
+    /// assert_eq!(endpoint.to_path("image.jpg"), "/300x200/image.jpg");
+    /// ```
+    pub fn new_unsigned(origin: impl Into<String>) -> Self {
+        Server {
+            origin: origin.into(),
+            security: Security::None,
+            base_path: String::new(),
+            min_signature_len: None,
+        }
+    }
+
+    /// Mounts Thumbor under a sub-path, as in `/images/<signature>/...`.
+    ///
+    /// Like Thumbor behind a reverse proxy, the base path is not part of the signed path.
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// let server = Server::new_unsafe("http://localhost:8888").with_base_path("/images/");
+    /// let endpoint = server.endpoint_builder().build();
+    ///
+    /// assert_eq!(endpoint.to_url("a.jpg"), "http://localhost:8888/images/unsafe/a.jpg");
+    /// ```
+    #[must_use]
+    pub fn with_base_path(self, base_path: impl Into<String>) -> Self {
+        Self {
+            base_path: base_path.into(),
+            ..self
+        }
+    }
+
+    /// Accepts signatures truncated to `min_len` characters or more when verifying URLs, as built
+    /// with [`EndpointBuilder::signature_len`](crate::EndpointBuilder::signature_len).
+    ///
+    /// Only full signatures are accepted by default. Every character dropped makes a signature
+    /// 64 times easier to forge: keep `min_len` as high as the setup allows.
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// let server = Server::new("http://localhost:8888", "my-security-key").unwrap();
+    /// let url = server.endpoint_builder().signature_len(16).build().to_url("a.jpg");
+    ///
+    /// assert!(!server.verify_url(&url));
+    /// assert!(server.truncated_signatures(16).verify_url(&url));
+    /// ```
+    #[must_use]
+    pub fn truncated_signatures(self, min_len: usize) -> Self {
+        Self {
+            min_signature_len: Some(min_len),
+            ..self
+        }
+    }
+
+    /// The base path, without surrounding slashes.
+    pub(crate) fn base_path(&self) -> &str {
+        self.base_path.trim_matches('/')
+    }
+
+    /// Rejects origins that do not use `https`, so that signed URLs are never served in plaintext.
+    ///
+    /// Servers are permissive by default, this has to be opted into.
+    ///
+    /// ```
+    /// use thumbor::Server;
+    ///
+    /// assert!(Server::new_unsafe("https://localhost:8888").require_https().is_ok());
+    /// assert!(Server::new_unsafe("http://localhost:8888").require_https().is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InsecureOrigin`] if the scheme of the origin is not `https`.
+    pub fn require_https(self) -> Result<Self, Error> {
+        let is_https = self
+            .origin
+            .get(.."https://".len())
+            .map_or(false, |scheme| scheme.eq_ignore_ascii_case("https://"));
+
+        if is_https {
+            Ok(self)
+        } else {
+            Err(Error::InsecureOrigin(self.origin))
         }
     }
 
@@ -60,6 +232,26 @@ impl Server {
     /// let server = Server::new("http://localhost:8888", "my-security-key").unwrap();
     /// let builder = server.endpoint_builder();
     /// ```
+    ///
+    /// A complete pipeline:
+    ///
+    /// ```
+    /// use thumbor::{endpoint::FitIn, Filter, Server};
+    ///
+    /// let server = Server::new("http://localhost:8888", "my-security-key").unwrap();
+    ///
+    /// let endpoint = server.endpoint_builder()
+    ///     .fit_in(FitIn::Default)
+    ///     .resize((300, 200))
+    ///     .smart(true)
+    ///     .filters([Filter::Brightness(10), Filter::Contrast(20)])
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     endpoint.to_path("path/to/my/image.jpg"),
+    ///     "/tGVyk_x4q6yUf7RxkNX6hN2CYgw=/fit-in/300x200/smart/filters:brightness(10):contrast(20)/path/to/my/image.jpg"
+    /// );
+    /// ```
     pub fn endpoint_builder(&self) -> EndpointBuilder {
         Endpoint::with_server(self.clone())
     }
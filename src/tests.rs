@@ -1,7 +1,15 @@
 use crate::{
-    endpoint::{FitIn, ResponseMode},
-    EndpointBuilder, Filter, Server,
+    endpoint::{
+        CompiledEndpoint, EndpointTemplate, FitIn, HAlignment, ResponseMode, Segment, Signature,
+        Trim, TrimOrigin, UriEncoding, VAlignment, Warning,
+    },
+    error::{ColorParseError, Error, FilterError},
+    filter::{Color, Convolution, DeliveryOptions, Format, Radius, Rotation, WatermarkTiled},
+    geometry::{CropRect, Dimension, Point, Rect, Resize},
+    metadata::{FocalPoint, Meta, Operation},
+    Endpoint, EndpointBuilder, Filter, Server,
 };
+use std::sync::Arc;
 
 const TEST_BASE: &str = "http://my.server.com";
 const SECURITY_KEY: &str = "my-security-key";
@@ -13,6 +21,181 @@ fn new_builder() -> EndpointBuilder {
         .endpoint_builder()
 }
 
+#[test]
+fn server_from_bytes() {
+    use base64ct::{Base64Url, Encoding};
+    use hmac::Mac;
+
+    // Not valid UTF-8.
+    let key = [0xff, 0xfe, 0x00, 0x80];
+
+    let server = Server::new_from_bytes(TEST_BASE, &key).expect("Server creation failed");
+    let path = server
+        .endpoint_builder()
+        .resize((300, 200))
+        .build()
+        .to_path(IMAGE_PATH);
+
+    let mut mac = crate::server::HmacSha1::new_from_slice(&key).expect("Invalid key");
+    mac.update(format!("300x200/{IMAGE_PATH}").as_bytes());
+    let signature = Base64Url::encode_string(&mac.finalize().into_bytes());
+    assert_eq!(path, format!("/{signature}/300x200/{IMAGE_PATH}"));
+
+    assert_eq!(
+        Server::new_from_bytes(TEST_BASE, SECURITY_KEY.as_bytes()).ok(),
+        Server::new(TEST_BASE, SECURITY_KEY).ok()
+    );
+}
+
+#[test]
+fn base_path_is_not_signed() {
+    let endpoint = new_builder().resize((300, 200)).build();
+    let path = endpoint.to_path(IMAGE_PATH);
+
+    for base_path in ["images", "/images/", "/images"] {
+        let server = Server::new(TEST_BASE, SECURITY_KEY)
+            .expect("Server creation failed")
+            .with_base_path(base_path);
+        let endpoint = server.endpoint_builder().resize((300, 200)).build();
+
+        assert_eq!(endpoint.to_path(IMAGE_PATH), format!("/images{path}"));
+        assert_eq!(
+            endpoint.to_path_no_leading_slash(IMAGE_PATH),
+            format!("images{path}")
+        );
+        assert_eq!(
+            endpoint.compiled().path(IMAGE_PATH),
+            endpoint.to_path(IMAGE_PATH)
+        );
+        assert_eq!(
+            endpoint.estimated_url_len(IMAGE_PATH),
+            endpoint.to_url(IMAGE_PATH).len()
+        );
+    }
+}
+
+#[test]
+fn signature_comparison() {
+    let a = Signature::new("GQTHuhtLqz7lQ3LZdlrHB1dH3oM=");
+
+    assert_eq!(a, Signature::new("GQTHuhtLqz7lQ3LZdlrHB1dH3oM="));
+    assert_ne!(a, Signature::new("XQTHuhtLqz7lQ3LZdlrHB1dH3oM="));
+    assert_ne!(a, Signature::new("GQTHuhtLqz7lQ3LZdlrHB1dH3oX="));
+    assert_ne!(a, Signature::new("GQTHuhtLqz7lQ3LZdlrHB1dH3oM"));
+    assert_ne!(a, Signature::new(""));
+    assert_eq!(Signature::new(""), Signature::new(""));
+    assert_eq!(a.to_string(), a.as_str());
+}
+
+#[test]
+fn verify_url() {
+    let server = Server::new(TEST_BASE, SECURITY_KEY).expect("Server creation failed");
+    let endpoint = server
+        .endpoint_builder()
+        .resize((300, 200))
+        .smart(true)
+        .build();
+    let url = endpoint.to_url(IMAGE_PATH);
+
+    assert!(server.verify_url(&url));
+    assert!(server.verify_url(&endpoint.to_path(IMAGE_PATH)));
+    assert_eq!(
+        url,
+        format!(
+            "{TEST_BASE}/{}/300x200/smart/{IMAGE_PATH}",
+            endpoint.signature(IMAGE_PATH)
+        )
+    );
+
+    let other = Server::new(TEST_BASE, "another-key").expect("Server creation failed");
+    assert!(!other.verify_url(&url));
+    assert!(!server.verify_url(&url.replace("smart/", "")));
+    assert!(!server.verify_url(&url.replace(IMAGE_PATH, "other.jpg")));
+    assert!(!server.verify_url("not a url"));
+    assert!(!server.verify_url(
+        &Server::new_unsafe(TEST_BASE)
+            .endpoint_builder()
+            .build()
+            .to_url("a.jpg")
+    ));
+
+    let unsafe_server = Server::new_unsafe(TEST_BASE);
+    assert!(unsafe_server.verify_url(&unsafe_server.endpoint_builder().build().to_url("a.jpg")));
+
+    let mounted = server.clone().with_base_path("images");
+    let url = mounted
+        .endpoint_builder()
+        .resize((300, 200))
+        .build()
+        .to_url("a.jpg");
+    assert!(mounted.verify_url(&url));
+    assert!(!server.verify_url(&url));
+}
+
+#[test]
+fn verify_with_rotated_keys() {
+    let url = Server::new(TEST_BASE, "new-key")
+        .expect("Server creation failed")
+        .endpoint_builder()
+        .resize((300, 200))
+        .build()
+        .to_url(IMAGE_PATH);
+
+    let server = Server::new(TEST_BASE, SECURITY_KEY).expect("Server creation failed");
+    assert!(!server.verify_url(&url));
+    assert!(server.verify_with_keys(&url, &["old-key", "new-key"]));
+    assert!(!server.verify_with_keys(&url, &["old-key"]));
+    assert!(!server.verify_with_keys(&url, &[]));
+    assert!(!server.verify_with_keys(&url.replace("300x200", "600x400"), &["old-key", "new-key"]));
+    assert!(!server.verify_with_keys("not a url", &["new-key"]));
+}
+
+#[test]
+fn to_url_is_idempotent() {
+    let endpoints = [
+        Server::new_unsafe(TEST_BASE)
+            .endpoint_builder()
+            .resize((300, 200))
+            .build(),
+        new_builder().resize((300, 200)).build(),
+        new_builder()
+            .smart(true)
+            .filters([Filter::Grayscale, Filter::Quality(80)])
+            .build(),
+    ];
+
+    for endpoint in endpoints {
+        let first = endpoint.to_url(IMAGE_PATH);
+        assert_eq!(endpoint.to_url(IMAGE_PATH), first);
+        assert_eq!(endpoint.to_url(IMAGE_PATH), first);
+
+        let other = endpoint.to_url("other.jpg");
+        assert_ne!(other, first);
+        assert_eq!(endpoint.to_url(IMAGE_PATH), first);
+        assert_eq!(endpoint.to_url("other.jpg"), other);
+    }
+}
+
+#[test]
+fn require_https() {
+    let http = || Server::new(TEST_BASE, SECURITY_KEY).expect("Server creation failed");
+    assert!(http()
+        .endpoint_builder()
+        .build()
+        .to_url("a.jpg")
+        .starts_with("http://"));
+    assert!(matches!(
+        http().require_https(),
+        Err(Error::InsecureOrigin(origin)) if origin == TEST_BASE
+    ));
+
+    for origin in ["https://localhost:8888", "HTTPS://localhost:8888"] {
+        let server = Server::new(origin, SECURITY_KEY).expect("Server creation failed");
+        assert_eq!(server.clone().require_https().ok(), Some(server));
+    }
+    assert!(Server::new_unsafe("my.server.com").require_https().is_err());
+}
+
 #[test]
 fn signing_of_a_known_url_results() {
     let width = 300;
@@ -77,3 +260,2643 @@ fn signature_with_filters() {
         "/ZZtPCw-BLYN1g42Kh8xTcRs0Qls=/filters:brightness(10):contrast(20)/my.server.com/some/path/to/image.jpg"
     );
 }
+
+#[test]
+fn path_without_leading_slash() {
+    let endpoint = new_builder().resize((300, 200)).build();
+
+    let path = endpoint.to_path_no_leading_slash(IMAGE_PATH);
+
+    assert!(!path.starts_with('/'));
+    assert_eq!(format!("/{path}"), endpoint.to_path(IMAGE_PATH));
+}
+
+#[test]
+fn parse_url_without_response() {
+    let url = format!("{TEST_BASE}/unsafe/{IMAGE_PATH}");
+
+    let (endpoint, image_uri) = Endpoint::from_url(&url).expect("Parsing failed");
+
+    assert_eq!(image_uri, IMAGE_PATH);
+    assert_eq!(endpoint.to_url(&image_uri), url);
+}
+
+#[test]
+fn parse_url_with_meta() {
+    let url = format!("{TEST_BASE}/unsafe/meta/{IMAGE_PATH}");
+
+    let (endpoint, image_uri) = Endpoint::from_url(&url).expect("Parsing failed");
+
+    assert_eq!(image_uri, IMAGE_PATH);
+    assert_eq!(endpoint.to_url(&image_uri), url);
+}
+
+#[test]
+fn parse_url_with_debug() {
+    let path = format!("/unsafe/debug/{IMAGE_PATH}");
+
+    let (endpoint, image_uri) = Endpoint::from_url(&path).expect("Parsing failed");
+
+    assert_eq!(image_uri, IMAGE_PATH);
+    assert_eq!(endpoint.to_path(&image_uri), path);
+}
+
+#[test]
+fn parse_url_without_image() {
+    assert!(Endpoint::from_url("/unsafe/debug/").is_err());
+    assert!(Endpoint::from_url("my.server.com/unsafe/image.jpg").is_err());
+}
+
+#[test]
+fn parse_url_of_unsigned_server() {
+    let server = Server::new_unsigned(TEST_BASE);
+    let endpoint = server
+        .endpoint_builder()
+        .resize((300, 200))
+        .smart(true)
+        .filter(Filter::Grayscale)
+        .build();
+    let url = endpoint.to_url(IMAGE_PATH);
+
+    let (parsed, image_uri) = Endpoint::from_url(&url).expect("Parsing failed");
+    assert_eq!(image_uri, IMAGE_PATH);
+    assert_eq!(parsed.to_url(&image_uri), url);
+
+    let (parsed, image_uri) = server.parse_url(&url).expect("Parsing failed");
+    assert_eq!(image_uri, IMAGE_PATH);
+    assert_eq!(parsed.to_url(&image_uri), url);
+
+    // A first segment that looks like neither `unsafe` nor a signature is an option.
+    let (parsed, image_uri) = Endpoint::from_url("/300x200/a.jpg").expect("Parsing failed");
+    assert_eq!(image_uri, "a.jpg");
+    assert_eq!(parsed.to_path("a.jpg"), "/300x200/a.jpg");
+}
+
+#[test]
+fn parse_url_with_base_path() {
+    let server = Server::new(TEST_BASE, SECURITY_KEY)
+        .expect("valid key")
+        .with_base_path("/images/");
+    let endpoint = server
+        .endpoint_builder()
+        .resize((300, 200))
+        .filter(Filter::Grayscale)
+        .build();
+    let url = endpoint.to_url(IMAGE_PATH);
+
+    let (parsed, image_uri) = server.parse_url(&url).expect("Parsing failed");
+    assert_eq!(image_uri, IMAGE_PATH);
+    assert_eq!(parsed.to_url(&image_uri), url);
+    assert!(server.verify_url(&parsed.to_url(&image_uri)));
+
+    let truncated = server
+        .endpoint_builder()
+        .signature_len(8)
+        .resize((300, 200))
+        .build();
+    let url = truncated.to_url(IMAGE_PATH);
+    let (parsed, image_uri) = server.parse_url(&url).expect("Parsing failed");
+    assert_eq!(image_uri, IMAGE_PATH);
+    // The signature is computed again, at its full length.
+    assert_eq!(
+        parsed.to_path(&image_uri),
+        server
+            .endpoint_builder()
+            .resize((300, 200))
+            .build()
+            .to_path(IMAGE_PATH)
+    );
+
+    let unsafe_server = Server::new_unsafe(TEST_BASE).with_base_path("images");
+    let url = unsafe_server
+        .endpoint_builder()
+        .fit_in(FitIn::Default)
+        .build()
+        .to_url(IMAGE_PATH);
+    let (parsed, image_uri) = unsafe_server.parse_url(&url).expect("Parsing failed");
+    assert_eq!(parsed.to_url(&image_uri), url);
+
+    assert!(matches!(
+        server.parse_url(&format!("{TEST_BASE}/other/unsafe/a.jpg")),
+        Err(Error::InvalidUrl(_))
+    ));
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn parse_http_uri() {
+    let url = format!("{TEST_BASE}/unsafe/fit-in/300x200/smart/{IMAGE_PATH}");
+    let uri = url.parse::<http::Uri>().expect("Invalid URI");
+
+    let (endpoint, image_uri) = Endpoint::from_uri(&uri).expect("Parsing failed");
+
+    assert_eq!(image_uri, IMAGE_PATH);
+    assert_eq!(endpoint.to_url(&image_uri), url);
+
+    let uri = http::Uri::from_static("/unsafe/debug/image.jpg");
+    let (endpoint, image_uri) = Endpoint::from_uri(&uri).expect("Parsing failed");
+    assert_eq!(endpoint.to_path(&image_uri), "/unsafe/debug/image.jpg");
+}
+
+#[test]
+fn max_bytes_above_u32() {
+    let filter = Filter::MaxBytes(u64::from(u32::MAX) + 1);
+
+    assert_eq!(filter.to_string(), "max_bytes(4294967296)");
+}
+
+#[test]
+fn zero_sized_resize() {
+    let endpoint = new_builder().resize((0, 0)).build();
+
+    let path = endpoint.to_path(IMAGE_PATH);
+
+    assert!(path.ends_with(&format!("/0x0/{IMAGE_PATH}")));
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn path_for_http_uri() {
+    let endpoint = new_builder().resize((300, 200)).build();
+
+    let uri = http::Uri::from_static("http://my.server.com/some/path/to/image.jpg");
+    assert_eq!(endpoint.to_path_for_uri(&uri), endpoint.to_path(IMAGE_PATH));
+
+    let uri = http::Uri::from_static("/some/path/to/image.jpg");
+    assert_eq!(
+        endpoint.to_path_for_uri(&uri),
+        endpoint.to_path("some/path/to/image.jpg")
+    );
+}
+
+#[test]
+fn color_approx_eq() {
+    let red = Color::Name("red".to_string());
+
+    assert!(red.approx_eq(&Color::Rgb(255, 0, 0)));
+    assert!(Color::Rgb(255, 0, 0).approx_eq(&red));
+    assert!(!red.approx_eq(&Color::Rgb(0, 0, 255)));
+    assert!(Color::Name("tomato".to_string()).approx_eq(&Color::Name("Tomato".to_string())));
+    assert_ne!(red, Color::Rgb(255, 0, 0));
+}
+
+#[test]
+fn paths_for_many_images() {
+    let endpoint = new_builder().resize((300, 200)).smart(true).build();
+    let image_uris = ["a.jpg", "b/c.jpg", IMAGE_PATH];
+
+    let paths = endpoint
+        .paths_for(image_uris.into_iter())
+        .collect::<Vec<_>>();
+
+    let expected = image_uris.map(|image_uri| endpoint.to_path(image_uri));
+    assert_eq!(paths, expected);
+}
+
+#[test]
+fn compiled_endpoint() {
+    let endpoints = [
+        new_builder().build(),
+        new_builder().resize((300, 200)).build(),
+        new_builder()
+            .resize((300, 200))
+            .smart(true)
+            .filter(Filter::Grayscale)
+            .build(),
+        Server::new_unsafe(TEST_BASE)
+            .endpoint_builder()
+            .resize((300, 200))
+            .build(),
+    ];
+
+    for endpoint in endpoints {
+        let compiled = endpoint.compiled();
+        for image_uri in ["a.jpg", "b/c.jpg", IMAGE_PATH] {
+            assert_eq!(compiled.path(image_uri), endpoint.to_path(image_uri));
+        }
+    }
+}
+
+#[test]
+fn shared_compiled_endpoint() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<CompiledEndpoint>();
+
+    let endpoint = new_builder().resize((300, 200)).smart(true).build();
+    let expected: Vec<_> = (0..32)
+        .map(|i| endpoint.to_path(format!("{i}.jpg")))
+        .collect();
+
+    let shared = endpoint.into_shared();
+    let handles: Vec<_> = (0..4)
+        .map(|thread| {
+            let shared = Arc::clone(&shared);
+            std::thread::spawn(move || {
+                (thread * 8..(thread + 1) * 8)
+                    .map(|i| shared.path(&format!("{i}.jpg")))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+
+    let paths: Vec<_> = handles
+        .into_iter()
+        .flat_map(|handle| handle.join().expect("thread panicked"))
+        .collect();
+    assert_eq!(paths, expected);
+}
+
+#[test]
+fn to_path_into_reuses_buffer() {
+    let endpoint = new_builder().resize((300, 200)).smart(true).build();
+    let mut path = String::new();
+
+    for image_uri in ["a.jpg", "b/c.jpg", IMAGE_PATH] {
+        path.clear();
+        endpoint.to_path_into(&mut path, image_uri);
+        assert_eq!(path, endpoint.to_path(image_uri));
+    }
+
+    endpoint.to_path_into(&mut path, "a.jpg");
+    assert_eq!(
+        path,
+        format!(
+            "{}{}",
+            endpoint.to_path(IMAGE_PATH),
+            endpoint.to_path("a.jpg")
+        )
+    );
+}
+
+#[test]
+fn map_filters_caps_quality() {
+    let mut endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .filters([
+            Filter::Quality(95),
+            Filter::Grayscale,
+            Filter::Quality(60),
+            Filter::Blur {
+                radius: 7,
+                sigma: None,
+            },
+            Filter::Quality(100),
+        ])
+        .build();
+
+    endpoint.map_filters(|filter| match filter {
+        Filter::Quality(quality) => Filter::Quality(quality.min(85)),
+        filter => filter,
+    });
+
+    assert_eq!(
+        endpoint.to_path(IMAGE_PATH),
+        format!(
+            "/unsafe/filters:quality(85):grayscale():quality(60):blur(7):quality(85)/{IMAGE_PATH}"
+        )
+    );
+}
+
+#[test]
+fn soft_blur_preset() {
+    let endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .filters(Filter::soft_blur(5))
+        .build();
+
+    assert_eq!(
+        endpoint.to_path(IMAGE_PATH),
+        format!("/unsafe/filters:blur(5):noise(3)/{IMAGE_PATH}")
+    );
+}
+
+#[test]
+fn join_filesystem_path() {
+    assert_eq!(
+        Endpoint::join_filesystem_path(&["a", "b", "c.jpg"]),
+        "a/b/c.jpg"
+    );
+    assert_eq!(
+        Endpoint::join_filesystem_path(&["/a/", "", "/b", "c d.jpg"]),
+        "a/b/c d.jpg"
+    );
+    assert_eq!(
+        Endpoint::join_filesystem_path(&["a/b", "c.jpg"]),
+        "a/b/c.jpg"
+    );
+    assert_eq!(Endpoint::join_filesystem_path(&[]), "");
+
+    let endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .resize((300, 200))
+        .build();
+    assert_eq!(
+        endpoint.to_path(Endpoint::join_filesystem_path(&["a", "b", "c.jpg"])),
+        "/unsafe/300x200/a/b/c.jpg"
+    );
+}
+
+#[test]
+fn format_chain() {
+    let chain = Filter::FormatChain(vec![Format::Webp, Format::Jpeg]);
+    assert_eq!(chain.name(), "format");
+    assert_eq!(chain.args(), ["webp", "jpeg"]);
+
+    let endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .filter(chain.clone())
+        .build();
+    assert_eq!(
+        endpoint.to_path(IMAGE_PATH),
+        format!("/unsafe/filters:format(webp,jpeg)/{IMAGE_PATH}")
+    );
+
+    assert_eq!("format(webp,jpeg)".parse::<Filter>().ok(), Some(chain));
+    assert_eq!(
+        "format(webp)".parse::<Filter>().ok(),
+        Some(Filter::Format(Format::Webp))
+    );
+    assert!("format(webp,bmp)".parse::<Filter>().is_err());
+    assert!(!Filter::variants().any(|name| name == "formatchain"));
+
+    let chains = [
+        vec![Format::Webp],
+        vec![Format::Avif, Format::Webp],
+        vec![Format::Avif, Format::Webp, Format::Jpeg],
+    ];
+    for formats in chains {
+        let filter = Filter::format_chain(formats).expect("valid chain");
+        assert_eq!(filter.to_string().parse::<Filter>().ok(), Some(filter));
+    }
+    assert_eq!(
+        Filter::format_chain([Format::Png]),
+        Ok(Filter::Format(Format::Png))
+    );
+    assert_eq!(
+        Filter::format_chain([]),
+        Err(FilterError::Invalid("format()".to_string()))
+    );
+}
+
+#[test]
+fn query_params_are_not_signed() {
+    let endpoint = new_builder().resize((300, 200)).build();
+    let with_query = new_builder()
+        .resize((300, 200))
+        .query_param("dpr", "2")
+        .query_param("a&b", "c=d")
+        .build();
+
+    let path = endpoint.to_path(IMAGE_PATH);
+    assert_eq!(with_query.to_path(IMAGE_PATH), path);
+    assert_eq!(
+        with_query.to_url(IMAGE_PATH),
+        format!("{TEST_BASE}{path}?dpr=2&a%26b=c%3Dd")
+    );
+    assert_eq!(
+        with_query.estimated_url_len(IMAGE_PATH),
+        with_query.to_url(IMAGE_PATH).len()
+    );
+
+    let server = Server::new(TEST_BASE, SECURITY_KEY).expect("valid key");
+    let url = endpoint.to_url(IMAGE_PATH);
+    assert!(server.verify_url(&url));
+    assert!(!server.verify_url(&with_query.to_url(IMAGE_PATH)));
+    assert!(!server.verify_url(&format!("{url}?anything")));
+    assert!(!server.verify_with_keys(&format!("{url}?anything"), &[SECURITY_KEY]));
+
+    let image_uri = "my.server.com/image.jpg?v=2";
+    assert!(server.verify_url(&endpoint.to_url(image_uri)));
+    assert!(!server.verify_url(&endpoint.to_url(image_uri).replace("v=2", "v=3")));
+    assert!(!server.verify_url(
+        &endpoint
+            .to_url("my.server.com/image.jpg")
+            .replace(".jpg", ".jpg?v=2")
+    ));
+}
+
+#[test]
+fn truncated_signature() {
+    let full = new_builder().resize((300, 200)).build().to_path(IMAGE_PATH);
+    let signature = full.split('/').nth(1).expect("missing signature");
+    assert_eq!(signature.len(), 28);
+
+    let endpoint = new_builder().resize((300, 200)).signature_len(10).build();
+    let path = endpoint.to_path(IMAGE_PATH);
+    assert_eq!(path, full.replacen(signature, &signature[..10], 1));
+    assert_eq!(path.split('/').nth(1).map(str::len), Some(10));
+    assert_eq!(
+        endpoint.estimated_url_len(IMAGE_PATH),
+        endpoint.to_url(IMAGE_PATH).len()
+    );
+
+    let server = Server::new(TEST_BASE, SECURITY_KEY).expect("valid key");
+    assert!(!server.verify_url(&endpoint.to_url(IMAGE_PATH)));
+    for len in [1, 2] {
+        let prefix = full.replacen(signature, &signature[..len], 1);
+        assert!(!server.verify_url(&format!("{TEST_BASE}{prefix}")));
+        assert!(!server.verify_with_keys(&format!("{TEST_BASE}{prefix}"), &[SECURITY_KEY]));
+    }
+
+    let server = server.truncated_signatures(10);
+    assert!(server.verify_url(&endpoint.to_url(IMAGE_PATH)));
+    assert!(server.verify_url(&endpoint.to_url(IMAGE_PATH).replacen(
+        &signature[..10],
+        signature,
+        1
+    )));
+    let prefix = full.replacen(signature, &signature[..9], 1);
+    assert!(!server.verify_url(&format!("{TEST_BASE}{prefix}")));
+
+    let forged = path.replacen(&signature[..10], &signature[1..11], 1);
+    assert!(!server.verify_url(&format!("{TEST_BASE}{forged}")));
+
+    let endpoint = new_builder().resize((300, 200)).signature_len(0).build();
+    let path = endpoint.to_path(IMAGE_PATH);
+    assert_eq!(path, full.replacen(signature, &signature[..1], 1));
+    assert_eq!(
+        endpoint.estimated_url_len(IMAGE_PATH),
+        endpoint.to_url(IMAGE_PATH).len()
+    );
+
+    let endpoint = new_builder().resize((300, 200)).signature_len(64).build();
+    assert_eq!(endpoint.to_path(IMAGE_PATH), full);
+
+    let endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .signature_len(4)
+        .build();
+    assert_eq!(endpoint.to_path("a.jpg"), "/unsafe/a.jpg");
+}
+
+/// Runs `f` with a subscriber recording the fields of every event.
+#[cfg(feature = "tracing")]
+fn record_events<R>(f: impl FnOnce() -> R) -> (R, Vec<Vec<String>>) {
+    use std::sync::Mutex;
+    use tracing::{
+        field::{Field, Visit},
+        span, Event, Metadata, Subscriber,
+    };
+
+    #[derive(Default)]
+    struct Fields(Vec<String>);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn core::fmt::Debug) {
+            self.0.push(format!("{}={value:?}", field.name()));
+        }
+    }
+
+    #[derive(Default)]
+    struct Events(Arc<Mutex<Vec<Vec<String>>>>);
+
+    impl Subscriber for Events {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            self.0.lock().expect("poisoned lock").push(fields.0);
+        }
+
+        fn enter(&self, _: &span::Id) {}
+
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    let subscriber = Events::default();
+    let events = Arc::clone(&subscriber.0);
+
+    let result = tracing::subscriber::with_default(subscriber, f);
+
+    let events = events.lock().expect("poisoned lock").clone();
+    (result, events)
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn to_path_emits_debug_event() {
+    let endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .resize((300, 200))
+        .smart(true)
+        .filter(Filter::Grayscale)
+        .build();
+
+    let (path, events) = record_events(|| endpoint.to_path("a.jpg"));
+
+    assert_eq!(
+        events,
+        [[
+            "message=built thumbor path".to_string(),
+            format!("path={path:?}"),
+            r#"options=["resize", "smart", "filters"]"#.to_string(),
+            r#"filters=["grayscale"]"#.to_string(),
+        ]]
+    );
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn zero_resize_warns_in_debug_builds() {
+    let warning =
+        ["message=resize of 0x0 keeps the original size, dimensions may be uninitialized"];
+    let warned = |events: &[Vec<String>]| events.iter().any(|event| *event == warning);
+
+    let endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .resize((0, 0))
+        .build();
+    let (_, events) = record_events(|| endpoint.to_path("a.jpg"));
+    assert_eq!(warned(&events), cfg!(debug_assertions));
+
+    let endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .resize((300, 0))
+        .build();
+    let (_, events) = record_events(|| endpoint.to_path("a.jpg"));
+    assert!(!warned(&events));
+}
+
+#[test]
+fn normalize_filter_order() {
+    let mut endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .filters([
+            Filter::Format(Format::Webp),
+            Filter::Quality(80),
+            Filter::Brightness(10),
+            Filter::Grayscale,
+            Filter::Contrast(20),
+        ])
+        .build();
+
+    endpoint.normalize_filter_order();
+
+    assert_eq!(
+        endpoint.to_path(IMAGE_PATH),
+        format!(
+            "/unsafe/filters:brightness(10):grayscale():contrast(20):format(webp):quality(80)/{IMAGE_PATH}"
+        )
+    );
+}
+
+#[test]
+fn short_hex_colors() {
+    let shortenable = Color::Rgb(0xff, 0xcc, 0x00);
+    let not_shortenable = Color::Rgb(0xff, 0xcd, 0x00);
+    assert_eq!(shortenable.to_short_hex().as_deref(), Some("#fc0"));
+    assert_eq!(not_shortenable.to_short_hex(), None);
+    assert_eq!(Color::from("red").to_short_hex(), None);
+    assert_eq!("#fc0".parse::<Color>().ok(), Some(shortenable.clone()));
+
+    let filters = [
+        Filter::BackgroundColor(shortenable.clone()),
+        Filter::Fill {
+            color: not_shortenable,
+            fill_transparent: false,
+        },
+        Filter::RoundCorners {
+            radius: Radius::Circle(10),
+            color: shortenable,
+            transparent: false,
+        },
+    ];
+    let builder = || {
+        Server::new_unsafe(TEST_BASE)
+            .endpoint_builder()
+            .filters(filters.clone())
+    };
+
+    assert_eq!(
+        builder().build().to_path("a.jpg"),
+        "/unsafe/filters:backgroundcolor(#ffcc00):fill(#ffcd00):roundcorners(10,#ffcc00)/a.jpg"
+    );
+    let endpoint = builder().short_hex_colors(true).build();
+    assert_eq!(
+        endpoint.to_path("a.jpg"),
+        "/unsafe/filters:backgroundcolor(#fc0):fill(#ffcd00):roundcorners(10,#fc0)/a.jpg"
+    );
+    assert_eq!(
+        endpoint.filter_summary()[0],
+        ("backgroundcolor".to_string(), vec!["#fc0".to_string()])
+    );
+}
+
+#[test]
+fn segments_of_a_full_endpoint() {
+    let endpoint = new_builder()
+        .debug()
+        .trim(Trim::default())
+        .crop(Rect::new(10, 20, 300, 400))
+        .fit_in(FitIn::Full)
+        .resize((300, 200))
+        .h_align(HAlignment::Left)
+        .v_align(VAlignment::Top)
+        .smart(true)
+        .filters([Filter::Grayscale, Filter::Quality(80)])
+        .uri_encoding(UriEncoding::Encode)
+        .build();
+
+    assert_eq!(
+        endpoint.segments("a b.jpg"),
+        [
+            Segment::Response(ResponseMode::Debug),
+            Segment::Trim(Trim::default()),
+            Segment::Crop(Rect::new(10, 20, 300, 400).into()),
+            Segment::FitIn(FitIn::Full),
+            Segment::Resize((300, 200).into()),
+            Segment::HAlign(HAlignment::Left),
+            Segment::VAlign(VAlignment::Top),
+            Segment::Smart,
+            Segment::Filters(vec![Filter::Grayscale, Filter::Quality(80)]),
+            Segment::Image("a%20b.jpg".to_string()),
+        ]
+    );
+
+    assert_eq!(
+        new_builder().build().segments(IMAGE_PATH),
+        [Segment::Image(IMAGE_PATH.to_string())]
+    );
+}
+
+#[test]
+fn filter_summary() {
+    let endpoint = new_builder()
+        .filters([
+            Filter::Brightness(10),
+            Filter::Fill {
+                color: Color::from("blue"),
+                fill_transparent: true,
+            },
+        ])
+        .build();
+
+    assert_eq!(
+        endpoint.filter_summary(),
+        [
+            ("brightness".to_string(), vec!["10".to_string()]),
+            (
+                "fill".to_string(),
+                vec!["blue".to_string(), "1".to_string()]
+            ),
+        ]
+    );
+    assert!(new_builder().build().filter_summary().is_empty());
+}
+
+#[test]
+fn estimated_url_len() {
+    let endpoints = [
+        new_builder().build(),
+        new_builder()
+            .resize((300, 200))
+            .uri_encoding(UriEncoding::Encode)
+            .build(),
+        Server::new_unsafe(TEST_BASE)
+            .endpoint_builder()
+            .smart(true)
+            .filter(Filter::Grayscale)
+            .build(),
+    ];
+
+    for endpoint in endpoints {
+        for image_uri in [IMAGE_PATH, "image.jpg?v=2"] {
+            assert_eq!(
+                endpoint.estimated_url_len(image_uri),
+                endpoint.to_url(image_uri).len()
+            );
+        }
+    }
+}
+
+#[test]
+fn long_filter_pipeline() {
+    let mut filters = vec![
+        Filter::watermark("https://cdn.example.com/w_100,h_50/logo.png", 10, 20, 50),
+        Filter::Blur {
+            radius: 3,
+            sigma: Some(2),
+        },
+        Filter::Fill {
+            color: Color::Rgb(0xff, 0xcc, 0x00),
+            fill_transparent: true,
+        },
+        Filter::Grayscale,
+        Filter::Custom {
+            name: "my_filter".to_string(),
+            args: vec!["a".to_string(), "b".to_string()],
+        },
+    ];
+    filters.extend((0..20).map(Filter::Brightness));
+
+    let expected = filters
+        .iter()
+        .map(|filter| format!("{}({})", filter.name(), filter.args().join(",")))
+        .collect::<Vec<_>>()
+        .join(":");
+
+    let endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .filters(filters)
+        .build();
+    assert_eq!(
+        endpoint.to_path(IMAGE_PATH),
+        format!("/unsafe/filters:{expected}/{IMAGE_PATH}")
+    );
+    assert_eq!(
+        endpoint.to_path_generic(IMAGE_PATH),
+        endpoint.to_path(IMAGE_PATH)
+    );
+}
+
+#[test]
+fn validate_url_len() {
+    let endpoint = new_builder()
+        .filters((0..100).map(Filter::Brightness))
+        .max_url_len(512)
+        .build();
+    let len = endpoint.to_url(IMAGE_PATH).len();
+
+    assert!(len > 512);
+    assert_eq!(
+        endpoint.validate(IMAGE_PATH),
+        [Warning::UrlTooLong { len, max: 512 }]
+    );
+
+    let endpoint = new_builder().max_url_len(512).build();
+    assert!(endpoint.validate(IMAGE_PATH).is_empty());
+
+    let endpoint = new_builder()
+        .filters((0..100).map(Filter::Brightness))
+        .build();
+    assert!(endpoint.validate(IMAGE_PATH).is_empty());
+}
+
+#[test]
+fn validate_smart_with_alignment() {
+    let endpoint = new_builder()
+        .resize((300, 200))
+        .smart(true)
+        .h_align(HAlignment::Left)
+        .build();
+    assert_eq!(
+        endpoint.validate(IMAGE_PATH),
+        [Warning::SmartOverridesAlignment]
+    );
+
+    let endpoint = new_builder()
+        .resize((300, 200))
+        .smart(true)
+        .v_align(VAlignment::Top)
+        .build();
+    assert_eq!(
+        endpoint.validate(IMAGE_PATH),
+        [Warning::SmartOverridesAlignment]
+    );
+
+    let endpoint = new_builder().resize((300, 200)).smart(true).build();
+    assert!(endpoint.validate(IMAGE_PATH).is_empty());
+
+    let endpoint = new_builder()
+        .resize((300, 200))
+        .h_align(HAlignment::Left)
+        .build();
+    assert!(endpoint.validate(IMAGE_PATH).is_empty());
+}
+
+#[test]
+fn validate_blur_and_sharpen() {
+    let sharpen = Filter::Sharpen {
+        sharpen_amount: 1.5,
+        sharpen_radius: 0.5,
+        luminance_only: true,
+    };
+
+    let endpoint = new_builder()
+        .filter(Filter::Blur {
+            radius: 3,
+            sigma: None,
+        })
+        .filter(sharpen.clone())
+        .build();
+    assert_eq!(endpoint.validate(IMAGE_PATH), [Warning::BlurAndSharpen]);
+    assert!(endpoint.try_to_path(IMAGE_PATH).is_ok());
+
+    let endpoint = new_builder().filter(sharpen).build();
+    assert!(endpoint.validate(IMAGE_PATH).is_empty());
+
+    let endpoint = new_builder().filters(Filter::soft_blur(3)).build();
+    assert!(endpoint.validate(IMAGE_PATH).is_empty());
+}
+
+#[test]
+fn validate_focal_without_resize() {
+    let focal = [Rect::new(10, 20, 30, 40)];
+
+    let endpoint = new_builder().focal_points(focal).build();
+    assert_eq!(endpoint.validate(IMAGE_PATH), [Warning::FocalWithoutResize]);
+
+    let endpoint = new_builder()
+        .resize(Resize::new(None, None))
+        .focal_points(focal)
+        .build();
+    assert_eq!(endpoint.validate(IMAGE_PATH), [Warning::FocalWithoutResize]);
+
+    let endpoint = new_builder().resize((300, 200)).focal_points(focal).build();
+    assert!(endpoint.validate(IMAGE_PATH).is_empty());
+    assert!(endpoint
+        .to_path(IMAGE_PATH)
+        .ends_with(&format!("/300x200/filters:focal(10x20:30x40)/{IMAGE_PATH}")));
+}
+
+#[test]
+fn crop_within_original_size() {
+    let endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .crop(Rect::new(100, 50, 900, 450))
+        .build();
+
+    assert!(endpoint
+        .validate_against_size(Point::new(1024, 768))
+        .is_ok());
+    assert!(endpoint.validate_against_size(Point::new(900, 450)).is_ok());
+    assert!(matches!(
+        endpoint.validate_against_size(Point::new(800, 600)),
+        Err(Error::CropOutOfBounds { crop, size })
+            if crop == Rect::new(100, 50, 900, 450) && size == Point::new(800, 600)
+    ));
+
+    let negative = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .crop(Rect::new(-10, 0, 100, 100))
+        .build();
+    assert!(negative
+        .validate_against_size(Point::new(800, 600))
+        .is_err());
+
+    let fractions = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .crop(CropRect::from_percentages(0.1, 0.1, 0.9, 0.9).expect("Invalid crop"))
+        .build();
+    assert!(fractions.validate_against_size(Point::new(10, 10)).is_ok());
+    assert!(Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .build()
+        .validate_against_size(Point::new(10, 10))
+        .is_ok());
+}
+
+#[test]
+fn endpoint_template() {
+    let endpoint = new_builder().resize((300, 200)).smart(true).build();
+    let template = EndpointTemplate::from(endpoint.clone());
+    let cloned = template.clone();
+
+    for image_uri in ["a.jpg", IMAGE_PATH] {
+        assert_eq!(template.render(image_uri), endpoint.to_path(image_uri));
+        assert_eq!(cloned.render_url(image_uri), endpoint.to_url(image_uri));
+    }
+}
+
+#[test]
+fn image_uri_with_query_string() {
+    let image_uri = "my.server.com/image.jpg?v=2&size=large#top";
+
+    let endpoint = new_builder().build();
+    assert!(endpoint
+        .to_path(image_uri)
+        .ends_with("/my.server.com/image.jpg?v=2&size=large#top"));
+
+    let endpoint = new_builder().uri_encoding(UriEncoding::Encode).build();
+    let path = endpoint.to_path(image_uri);
+    assert!(path.ends_with("/my.server.com/image.jpg%3Fv%3D2%26size%3Dlarge%23top"));
+    assert_eq!(endpoint.compiled().path(image_uri), path);
+
+    let endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .uri_encoding(UriEncoding::Encode)
+        .build();
+    assert_eq!(
+        endpoint.to_path("https://my.server.com/image 1.jpg"),
+        "/unsafe/https://my.server.com/image%201.jpg"
+    );
+}
+
+#[test]
+fn already_encoded_image_uri() {
+    let image_uri = "https://my.server.com/my%20image.jpg";
+    let path = |encoding| {
+        Server::new_unsafe(TEST_BASE)
+            .endpoint_builder()
+            .uri_encoding(encoding)
+            .build()
+            .to_path(image_uri)
+    };
+
+    assert_eq!(
+        path(UriEncoding::None),
+        "/unsafe/https://my.server.com/my%20image.jpg"
+    );
+    assert_eq!(
+        path(UriEncoding::Encode),
+        "/unsafe/https://my.server.com/my%2520image.jpg"
+    );
+    assert_eq!(
+        path(UriEncoding::AlreadyEncoded),
+        "/unsafe/https://my.server.com/my%20image.jpg"
+    );
+
+    let endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .uri_encoding(UriEncoding::AlreadyEncoded)
+        .build();
+    assert_eq!(
+        endpoint.to_path("my.server.com/my%20image 2.jpg?v=100%"),
+        "/unsafe/my.server.com/my%20image%202.jpg%3Fv%3D100%25"
+    );
+
+    let endpoint = new_builder()
+        .uri_encoding(UriEncoding::AlreadyEncoded)
+        .build();
+    assert_eq!(
+        endpoint.compiled().path(image_uri),
+        endpoint.to_path(image_uri)
+    );
+}
+
+#[test]
+fn unicode_image_uri() {
+    let image_uri = "my.server.com/фото/кот.jpg";
+
+    let endpoint = Server::new_unsafe(TEST_BASE).endpoint_builder().build();
+    assert_eq!(
+        endpoint.to_path(image_uri),
+        "/unsafe/my.server.com/фото/кот.jpg"
+    );
+
+    let endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .uri_encoding(UriEncoding::Encode)
+        .build();
+    assert_eq!(
+        endpoint.to_path(image_uri),
+        "/unsafe/my.server.com/%D1%84%D0%BE%D1%82%D0%BE/%D0%BA%D0%BE%D1%82.jpg"
+    );
+
+    let endpoint = new_builder().uri_encoding(UriEncoding::Encode).build();
+    let path = endpoint.to_path(image_uri);
+    assert!(path.is_ascii());
+    assert_eq!(endpoint.compiled().path(image_uri), path);
+}
+
+#[test]
+fn fit_in_adaptive() {
+    let endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .fit_in_adaptive((300, 200))
+        .smart(true)
+        .build();
+    assert_eq!(
+        endpoint.to_path(IMAGE_PATH),
+        format!("/unsafe/adaptive-fit-in/300x200/smart/{IMAGE_PATH}")
+    );
+
+    for fit_in in [
+        FitIn::Default,
+        FitIn::Adaptive,
+        FitIn::Full,
+        FitIn::AdaptiveFull,
+    ] {
+        assert!(fit_in.adaptive().to_string().starts_with("adaptive-"));
+        assert_eq!(fit_in.adaptive().adaptive(), fit_in.adaptive());
+    }
+}
+
+#[test]
+fn parse_fit_in() {
+    assert_eq!("fit-in".parse(), Ok(FitIn::Default));
+    assert_eq!("adaptive-fit-in".parse(), Ok(FitIn::Adaptive));
+    assert_eq!("full-fit-in".parse(), Ok(FitIn::Full));
+    assert_eq!("adaptive-full-fit-in".parse(), Ok(FitIn::AdaptiveFull));
+    assert!("fit".parse::<FitIn>().is_err());
+}
+
+#[test]
+fn trim_round_trip() {
+    for origin in [TrimOrigin::TopLeft, TrimOrigin::BottomRight] {
+        for tolerance in [0, 1, 15, 442] {
+            let trim = Trim { origin, tolerance };
+            assert_eq!(trim.to_string().parse::<Trim>().ok(), Some(trim));
+        }
+    }
+
+    assert_eq!(
+        Trim {
+            origin: TrimOrigin::TopLeft,
+            tolerance: 15
+        }
+        .to_string(),
+        "trim:top-left:15"
+    );
+    assert_eq!(Trim::default().to_string(), "trim:top-left");
+    assert_eq!("trim".parse::<Trim>().ok(), Some(Trim::default()));
+    assert_eq!(
+        "trim:15".parse::<Trim>().ok(),
+        Some(Trim {
+            origin: TrimOrigin::TopLeft,
+            tolerance: 15
+        })
+    );
+
+    for invalid in [
+        "trim:",
+        "trim:top-right",
+        "trim:top-left:",
+        "trim:-1",
+        "trim:1:2",
+        "trimmed",
+    ] {
+        assert!(invalid.parse::<Trim>().is_err(), "{invalid}");
+    }
+}
+
+#[test]
+fn trim_builder() {
+    let builder = || Server::new_unsafe(TEST_BASE).endpoint_builder();
+
+    assert_eq!(
+        builder().trim_default().build().to_path("a.jpg"),
+        "/unsafe/trim:top-left/a.jpg"
+    );
+    assert_eq!(
+        builder()
+            .trim(Trim {
+                origin: TrimOrigin::BottomRight,
+                tolerance: 20
+            })
+            .resize((300, 200))
+            .build()
+            .to_path("a.jpg"),
+        "/unsafe/trim:bottom-right:20/300x200/a.jpg"
+    );
+    assert_eq!(
+        builder()
+            .trim(TrimOrigin::BottomRight)
+            .build()
+            .to_path("a.jpg"),
+        "/unsafe/trim:bottom-right/a.jpg"
+    );
+}
+
+#[test]
+fn parse_url_with_trim() {
+    let path = format!("/unsafe/trim:bottom-right:20/300x200/{IMAGE_PATH}");
+
+    let (endpoint, image_uri) = Endpoint::from_url(&path).expect("Parsing failed");
+
+    assert_eq!(image_uri, IMAGE_PATH);
+    assert_eq!(endpoint.to_path(&image_uri), path);
+}
+
+#[test]
+fn parse_url_with_filters() {
+    let path = format!(
+        "/unsafe/300x200/smart/filters:watermark(http://my.server.com/logo.png,10,10,50):grayscale()/{IMAGE_PATH}"
+    );
+
+    let (endpoint, image_uri) = Endpoint::from_url(&path).expect("Parsing failed");
+
+    assert_eq!(image_uri, IMAGE_PATH);
+    assert_eq!(
+        endpoint.filter_summary()[1],
+        ("grayscale".to_string(), vec![])
+    );
+    assert_eq!(endpoint.to_path(&image_uri), path);
+}
+
+#[test]
+fn parse_url_with_fit_in() {
+    let path = format!("/unsafe/meta/adaptive-fit-in/{IMAGE_PATH}");
+
+    let (endpoint, image_uri) = Endpoint::from_url(&path).expect("Parsing failed");
+
+    assert_eq!(image_uri, IMAGE_PATH);
+    assert_eq!(endpoint.to_path(&image_uri), path);
+}
+
+#[test]
+fn parse_alignments() {
+    assert_eq!("left".parse(), Ok(HAlignment::Left));
+    assert_eq!("center".parse(), Ok(HAlignment::Center));
+    assert_eq!("right".parse(), Ok(HAlignment::Right));
+    assert!("middle".parse::<HAlignment>().is_err());
+
+    assert_eq!("top".parse(), Ok(VAlignment::Top));
+    assert_eq!("middle".parse(), Ok(VAlignment::Middle));
+    assert_eq!("bottom".parse(), Ok(VAlignment::Bottom));
+    assert!("center".parse::<VAlignment>().is_err());
+}
+
+#[test]
+fn parse_url_with_alignments() {
+    let path = format!("/unsafe/fit-in/right/bottom/smart/{IMAGE_PATH}");
+
+    let (endpoint, image_uri) = Endpoint::from_url(&path).expect("Parsing failed");
+
+    assert_eq!(image_uri, IMAGE_PATH);
+    assert_eq!(endpoint.to_path(&image_uri), path);
+}
+
+#[test]
+fn parse_resize() {
+    let px = |pixels| Some(Dimension::Pixels(pixels));
+
+    assert_eq!("300x200".parse().ok(), Some(Resize::from((300, 200))));
+    assert_eq!("-300x200".parse().ok(), Some(Resize::from((-300, 200))));
+    assert_eq!("300x-200".parse().ok(), Some(Resize::from((300, -200))));
+    assert_eq!("0x200".parse().ok(), Some(Resize::from((0, 200))));
+    assert_eq!("0x0".parse().ok(), Some(Resize::from((0, 0))));
+    assert_eq!("300x".parse().ok(), Some(Resize::new(px(300), None)));
+    assert_eq!("x200".parse().ok(), Some(Resize::new(None, px(200))));
+    assert_eq!(
+        "origx100".parse().ok(),
+        Some(Resize::new(Some(Dimension::Orig), px(100)))
+    );
+    assert_eq!(
+        "100xorig".parse().ok(),
+        Some(Resize::new(px(100), Some(Dimension::Orig)))
+    );
+    assert_eq!(
+        "-origx-orig".parse().ok(),
+        Some(
+            Resize::new(Some(Dimension::Orig), Some(Dimension::Orig))
+                .flip_horizontally()
+                .flip_vertically()
+        )
+    );
+    assert_eq!(
+        "-0x200"
+            .parse::<Resize>()
+            .ok()
+            .map(|resize| resize.to_string()),
+        Some("-0x200".to_string())
+    );
+
+    for invalid in [
+        "300",
+        "300x200x100",
+        "+300x200",
+        "ax200",
+        "--300x200",
+        "300.5x200",
+    ] {
+        assert!(invalid.parse::<Resize>().is_err(), "{invalid}");
+    }
+}
+
+#[test]
+fn parse_url_with_resize() {
+    let path = format!("/unsafe/fit-in/-origx200/left/{IMAGE_PATH}");
+
+    let (endpoint, image_uri) = Endpoint::from_url(&path).expect("Parsing failed");
+
+    assert_eq!(image_uri, IMAGE_PATH);
+    assert_eq!(endpoint.to_path(&image_uri), path);
+}
+
+#[test]
+fn parse_crop() {
+    assert_eq!(
+        "10x20:300x400".parse::<Rect>().ok(),
+        Some(Rect::new(10, 20, 300, 400))
+    );
+    assert_eq!(
+        "-10x-20:300x400".parse::<Rect>().ok(),
+        Some(Rect::new(-10, -20, 300, 400))
+    );
+
+    for invalid in [
+        "10x20",
+        "10x20:300",
+        "10x20:300x400:1x1",
+        "10:20:300:400",
+        "ax20:300x400",
+    ] {
+        assert!(invalid.parse::<Rect>().is_err(), "{invalid}");
+    }
+}
+
+#[test]
+fn crop_from_percentages() {
+    let crop = CropRect::from_percentages(0.1, 0.2, 0.9, 0.8).expect("valid crop");
+    assert_eq!(crop.to_string(), "0.1x0.2:0.9x0.8");
+    assert_eq!(
+        CropRect::from_percentages(0.0, 0.0, 1.0, 1.0)
+            .expect("valid crop")
+            .to_string(),
+        "0.0x0.0:1.0x1.0"
+    );
+    assert!(CropRect::from_percentages(0.5, 0.5, 0.5, 0.5).is_ok());
+
+    let endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .crop(crop)
+        .resize((300, 200))
+        .build();
+    assert_eq!(
+        endpoint.to_path(IMAGE_PATH),
+        format!("/unsafe/0.1x0.2:0.9x0.8/300x200/{IMAGE_PATH}")
+    );
+
+    for (left, top, right, bottom) in [
+        (-0.1, 0.2, 0.9, 0.8),
+        (0.1, 0.2, 1.1, 0.8),
+        (0.9, 0.2, 0.1, 0.8),
+        (0.1, 0.8, 0.9, 0.2),
+        (f32::NAN, 0.2, 0.9, 0.8),
+    ] {
+        assert!(matches!(
+            CropRect::from_percentages(left, top, right, bottom),
+            Err(Error::InvalidSegment { kind: "crop", .. })
+        ));
+    }
+}
+
+#[test]
+fn crop_display_modes() {
+    let pixels = CropRect::from(Rect::new(0, 0, 1, 1));
+    let fractions = CropRect::from_percentages(0.0, 0.0, 1.0, 1.0).expect("valid crop");
+    assert_eq!(pixels.to_string(), "0x0:1x1");
+    assert_eq!(fractions.to_string(), "0.0x0.0:1.0x1.0");
+    assert_ne!(pixels, fractions);
+
+    for crop in [
+        pixels,
+        fractions,
+        CropRect::from_percentages(0.25, 0.125, 0.75, 0.5).expect("valid crop"),
+    ] {
+        assert_eq!(crop.to_string().parse::<CropRect>().ok(), Some(crop));
+    }
+
+    for invalid in [
+        "0.1x20:300x0.8",
+        "0.1x0.2:300x400",
+        "0.1x0.2:0.9",
+        "0.9x0.2:0.1x0.8",
+        "1.5x0.2:0.9x0.8",
+    ] {
+        assert!(
+            matches!(
+                invalid.parse::<CropRect>(),
+                Err(Error::InvalidSegment { kind: "crop", .. })
+            ),
+            "{invalid}"
+        );
+    }
+
+    let path = format!("/unsafe/0.1x0.2:0.9x0.8/300x200/{IMAGE_PATH}");
+    let (endpoint, image_uri) = Endpoint::from_url(&path).expect("Parsing failed");
+    assert_eq!(endpoint.to_path(&image_uri), path);
+}
+
+#[test]
+fn parse_url_with_crop() {
+    let path = format!("/unsafe/debug/10x20:300x400/fit-in/300x200/{IMAGE_PATH}");
+
+    let (endpoint, image_uri) = Endpoint::from_url(&path).expect("Parsing failed");
+
+    assert_eq!(image_uri, IMAGE_PATH);
+    assert_eq!(endpoint.to_path(&image_uri), path);
+}
+
+#[test]
+fn diff_endpoints() {
+    let a = new_builder().resize((300, 200)).smart(true).build();
+    let b = new_builder().resize((400, 300)).smart(true).build();
+
+    assert_eq!(a.diff(&b), ["resize: 300x200 != 400x300"]);
+    assert!(a.diff(&a).is_empty());
+}
+
+#[test]
+fn multiple_focal_points() {
+    let endpoint = new_builder()
+        .focal_points([Rect::new(10, 10, 50, 50), Rect::new(100, 100, 200, 200)])
+        .build();
+
+    let path = endpoint.to_path(IMAGE_PATH);
+
+    assert!(path.contains("/filters:focal(10x10:50x50):focal(100x100:200x200)/"));
+}
+
+#[test]
+fn filters_are_appended() {
+    let endpoint = new_builder()
+        .filter(Filter::Brightness(10))
+        .resize((300, 200))
+        .filters([Filter::Contrast(20)])
+        .build();
+
+    let expected = new_builder()
+        .resize((300, 200))
+        .filters([Filter::Brightness(10), Filter::Contrast(20)])
+        .build();
+
+    assert_eq!(endpoint.to_path(IMAGE_PATH), expected.to_path(IMAGE_PATH));
+}
+
+#[test]
+fn rotations() {
+    assert_eq!(Filter::rotation(Rotation::None).to_string(), "rotate(0)");
+    assert_eq!(Filter::rotation(Rotation::Cw90).to_string(), "rotate(90)");
+    assert_eq!(Filter::rotation(Rotation::Cw180).to_string(), "rotate(180)");
+    assert_eq!(Filter::rotation(Rotation::Cw270).to_string(), "rotate(270)");
+}
+
+#[test]
+fn orthogonal_rotations() {
+    assert_eq!(Filter::rotate_orthogonal(90).ok(), Some(Filter::Rotate(90)));
+    assert_eq!(Filter::rotate_orthogonal(0).ok(), Some(Filter::Rotate(0)));
+    assert_eq!(Filter::rotate_orthogonal(360).ok(), Some(Filter::Rotate(0)));
+    assert_eq!(
+        Filter::rotate_orthogonal(630).ok(),
+        Some(Filter::Rotate(270))
+    );
+
+    for degrees in [45, 1, 359, 405] {
+        assert_eq!(
+            Filter::rotate_orthogonal(degrees),
+            Err(FilterError::NotOrthogonal(degrees))
+        );
+    }
+    assert_eq!(Filter::Rotate(45).to_string(), "rotate(45)");
+}
+
+#[test]
+fn color_serde_round_trip() {
+    for (color, json) in [
+        (Color::from("red"), r#""red""#),
+        (Color::Rgb(255, 0, 0), r##""#ff0000""##),
+        (Color::Auto, r#""auto""#),
+        (Color::Transparent, r#""transparent""#),
+    ] {
+        assert_eq!(
+            serde_json::to_string(&color).expect("serialization failed"),
+            json
+        );
+        assert_eq!(
+            serde_json::from_str::<Color>(json).expect("deserialization failed"),
+            color
+        );
+    }
+
+    assert_eq!(
+        serde_json::from_str::<Color>(r#""ff0000""#).expect("deserialization failed"),
+        Color::Rgb(255, 0, 0)
+    );
+    assert!(serde_json::from_str::<Color>(r#""not a color""#).is_err());
+    assert!(serde_json::from_str::<Color>("[255, 0, 0]").is_err());
+}
+
+#[test]
+fn nested_watermark() {
+    let logo = new_builder()
+        .resize((100, 50))
+        .filters([Filter::Grayscale, Filter::Quality(80)])
+        .build();
+    let filter = Filter::watermark_nested(&logo, "logo.png", -10, 20, 50).expect("valid watermark");
+    let inner = logo.to_url("logo.png");
+
+    assert_eq!(
+        filter.to_string(),
+        format!(
+            "watermark({},-10,20,50)",
+            inner
+                .replace('(', "%28")
+                .replace(')', "%29")
+                .replace('=', "%3D")
+        )
+    );
+    assert!(!filter.args()[0].contains(['(', ')']));
+
+    let endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .filter(filter)
+        .build();
+    let (parsed, _) = Endpoint::from_url(&endpoint.to_url(IMAGE_PATH)).expect("valid url");
+    assert_eq!(parsed.to_path(IMAGE_PATH), endpoint.to_path(IMAGE_PATH));
+
+    for origin in [
+        "",
+        "localhost:8888",
+        "/images",
+        "ftp://localhost",
+        "http://",
+    ] {
+        let logo = Server::new_unsafe(origin).endpoint_builder().build();
+        assert!(
+            matches!(
+                Filter::watermark_nested(&logo, "logo.png", 10, 20, 50),
+                Err(Error::InvalidUrl(_))
+            ),
+            "{origin}"
+        );
+    }
+    assert!(matches!(
+        Filter::watermark_nested(&logo, "logo.png", 10, 20, 101),
+        Err(Error::Filter(FilterError::OutOfRange {
+            argument: "alpha",
+            value: 101,
+            ..
+        }))
+    ));
+
+    for (x, y) in [(0, 0), (-10, -20), (-65_535, 65_535)] {
+        let filter =
+            Filter::watermark_nested(&logo, "logo.png", x, y, 100).expect("valid watermark");
+        assert_eq!(filter.args()[1..3], [x.to_string(), y.to_string()]);
+    }
+    for (x, y, argument, value) in [
+        (65_536, 0, "x", 65_536),
+        (0, -65_536, "y", -65_536),
+        (i32::MIN, 0, "x", i64::from(i32::MIN)),
+        (0, i32::MAX, "y", i64::from(i32::MAX)),
+    ] {
+        assert_eq!(
+            Filter::watermark_nested(&logo, "logo.png", x, y, 50)
+                .err()
+                .map(|error| error.to_string()),
+            Some(
+                FilterError::OutOfRange {
+                    argument,
+                    value,
+                    min: -65_535,
+                    max: 65_535
+                }
+                .to_string()
+            )
+        );
+    }
+}
+
+#[test]
+fn error_into_io_error() {
+    use std::io::ErrorKind;
+
+    let error = Endpoint::from_url("localhost/unsafe/a.jpg")
+        .err()
+        .expect("invalid url");
+    let io_error = std::io::Error::from(error);
+    assert_eq!(io_error.kind(), ErrorKind::InvalidInput);
+    assert_eq!(io_error.to_string(), "invalid thumbor URL: missing scheme");
+    assert!(io_error
+        .get_ref()
+        .map_or(false, |source| source.is::<Error>()));
+
+    let error = serde_json::from_str::<Point>("{}").expect_err("invalid point");
+    assert_eq!(
+        std::io::Error::from(Error::from(error)).kind(),
+        ErrorKind::InvalidData
+    );
+    assert_eq!(
+        std::io::Error::from(Error::UnsafeNotAllowed).kind(),
+        ErrorKind::PermissionDenied
+    );
+}
+
+#[test]
+fn deserialize_point() {
+    let expected = Point::new(800, 600);
+
+    let object: Point = serde_json::from_str(r#"{"width": 800, "height": 600}"#).expect("object");
+    assert_eq!(object, expected);
+
+    let array: Point = serde_json::from_str("[800, 600]").expect("array");
+    assert_eq!(array, expected);
+
+    assert!(serde_json::from_str::<Point>("[800]").is_err());
+}
+
+#[test]
+fn error_is_send_sync() {
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+
+    assert_send_sync::<Error>();
+}
+
+#[test]
+fn error_messages() {
+    let errors = [
+        Error::InvalidUrl("missing scheme"),
+        Error::invalid_segment("resize", "300"),
+        Error::NotMetadata,
+        Error::from(serde_json::from_str::<Point>("{}").expect_err("invalid point")),
+    ];
+
+    for error in errors {
+        assert!(!error.to_string().is_empty(), "{error:?}");
+    }
+}
+
+#[test]
+fn sorted_filters() {
+    let endpoint = new_builder()
+        .filters([
+            Filter::Quality(80),
+            Filter::Grayscale,
+            Filter::Brightness(10),
+            Filter::Focal(Rect::new(1, 1, 2, 2)),
+            Filter::Contrast(20),
+            Filter::Focal(Rect::new(0, 0, 1, 1)),
+        ])
+        .sort_filters(true)
+        .build();
+
+    let path = endpoint.to_path(IMAGE_PATH);
+
+    assert!(path.contains(
+        "/filters:brightness(10):contrast(20):focal(1x1:2x2):focal(0x0:1x1):grayscale():quality(80)/"
+    ));
+}
+
+#[test]
+fn argless_filters() {
+    assert!(Filter::Grayscale.is_argless());
+    assert!(Filter::Upscale.is_argless());
+    assert!(Filter::Custom {
+        name: "my_filter".to_string(),
+        args: vec![]
+    }
+    .is_argless());
+
+    let mut argless: Vec<_> = Filter::variants().filter_map(Filter::argless).collect();
+    for filter in &argless {
+        assert!(filter.is_argless());
+        assert!(filter.args().is_empty());
+        assert_eq!(filter.to_string(), format!("{}()", filter.name()));
+    }
+    // Only the argless filters can be written without arguments.
+    for name in Filter::variants() {
+        assert_eq!(
+            format!("{name}()").parse::<Filter>().ok(),
+            Filter::argless(name),
+            "{name}"
+        );
+    }
+
+    argless.sort_by_key(|filter| filter.name().to_string());
+    assert_eq!(
+        argless.iter().map(Filter::name).collect::<Vec<_>>(),
+        [
+            "autojpg",
+            "cover",
+            "equalize",
+            "extractfocalpoints",
+            "grayscale",
+            "noupscale",
+            "redeye",
+            "stretch",
+            "stripexif",
+            "stripicc",
+            "upscale",
+        ]
+    );
+
+    assert!(!Filter::Quality(1).is_argless());
+    assert!(!Filter::Fill {
+        color: Color::Name("blue".to_string()),
+        fill_transparent: false
+    }
+    .is_argless());
+}
+
+#[test]
+fn parse_url_with_meta_and_debug() {
+    let path = format!("/unsafe/meta/debug/{IMAGE_PATH}");
+
+    assert!(matches!(
+        Endpoint::from_url(&path),
+        Err(Error::InvalidUrl(_))
+    ));
+}
+
+#[test]
+fn resize_only_fast_path() {
+    let sizes = [0, 1, -1, 200, -300, 4096, i32::MAX, i32::MIN];
+
+    for width in sizes {
+        for height in sizes {
+            let endpoint = new_builder().resize((width, height)).build();
+
+            assert_eq!(endpoint.resize_only_path(), Some(endpoint.generic_path()));
+            assert_eq!(
+                endpoint.to_path(IMAGE_PATH),
+                endpoint.to_path_generic(IMAGE_PATH)
+            );
+        }
+    }
+
+    let endpoint = new_builder().resize((300, 200)).smart(true).build();
+    assert_eq!(endpoint.resize_only_path(), None);
+}
+
+#[test]
+fn color_from_rgb() {
+    assert_eq!(Color::from((255, 0, 0)).to_string(), "#ff0000");
+    assert_eq!(Color::from([255, 0, 0]).to_string(), "#ff0000");
+
+    let filter = Filter::BackgroundColor((255, 0, 0).into());
+    assert!(filter.to_string().ends_with("(#ff0000)"));
+}
+
+#[test]
+fn color_from_name() {
+    assert_eq!(Color::from("red"), Color::Name("red".to_string()));
+    assert_eq!(
+        Color::from("red".to_string()),
+        Color::Name("red".to_string())
+    );
+    assert_eq!(Color::from("auto"), Color::Auto);
+    assert_eq!(Color::from("blur".to_string()), Color::Blur);
+    assert_eq!(Color::from("transparent"), Color::Transparent);
+
+    assert_eq!(Color::from("auto").to_string(), "auto");
+    assert!(Color::Auto.approx_eq(&Color::Auto));
+    assert!(!Color::Auto.approx_eq(&Color::Blur));
+}
+
+#[test]
+fn color_named() {
+    assert_eq!(
+        Color::named("blue").ok(),
+        Some(Color::Name("blue".to_string()))
+    );
+    assert_eq!(
+        Color::named("RebeccaPurple").ok(),
+        Some(Color::Name("RebeccaPurple".to_string()))
+    );
+    assert_eq!(Color::named("auto").ok(), Some(Color::Auto));
+    assert_eq!(Color::named("ble"), Err(ColorParseError("ble".to_string())));
+    assert!(Color::named("").is_err());
+
+    assert!(Color::from("cornflowerblue").approx_eq(&Color::Rgb(0x64, 0x95, 0xed)));
+    assert!(Color::from("grey").approx_eq(&Color::from("gray")));
+}
+
+#[test]
+fn try_filters() {
+    let fill = |name| {
+        Color::named(name).map(|color| Filter::Fill {
+            color,
+            fill_transparent: false,
+        })
+    };
+
+    let endpoint = new_builder()
+        .try_filters([Ok(Filter::Grayscale), fill("blue")])
+        .expect("Invalid filters")
+        .build();
+    let expected = new_builder()
+        .filters([Filter::Grayscale, fill("blue").expect("Invalid filter")])
+        .build();
+    assert_eq!(endpoint.to_path(IMAGE_PATH), expected.to_path(IMAGE_PATH));
+
+    let result = new_builder().try_filters([Ok(Filter::Grayscale), fill("ble"), fill("red")]);
+    assert_eq!(result.err(), Some(ColorParseError("ble".to_string())));
+}
+
+#[test]
+fn background_color_auto() {
+    assert_eq!(
+        Filter::BackgroundColor(Color::Auto).to_string(),
+        "backgroundcolor(auto)"
+    );
+    assert_eq!(
+        Filter::BackgroundColor(Color::from("auto")).to_string(),
+        "backgroundcolor(auto)"
+    );
+    assert_eq!(
+        Filter::BackgroundColor(Color::from("blue")).to_string(),
+        "backgroundcolor(blue)"
+    );
+
+    let path = new_builder()
+        .filter(Filter::BackgroundColor(Color::Auto))
+        .build()
+        .to_path(IMAGE_PATH);
+    assert!(path.contains("/filters:backgroundcolor(auto)/"));
+}
+
+#[test]
+fn signature_without_filters() {
+    let endpoint = new_builder()
+        .resize((300, 200))
+        .smart(true)
+        .filters([])
+        .build();
+
+    let path = endpoint.to_path(IMAGE_PATH);
+
+    assert!(!path.contains("filters:"));
+    assert_eq!(
+        path,
+        "/a6-Wlrgfl_jW4YvfKIuVnmjEPhc=/300x200/smart/my.server.com/some/path/to/image.jpg"
+    );
+}
+
+#[test]
+fn watermark_with_parentheses() {
+    let filter = Filter::watermark("http://my.server.com/logo_(dark).png", -10, 20, 50);
+
+    assert_eq!(
+        filter.to_string(),
+        "watermark(http://my.server.com/logo_%28dark%29.png,-10,20,50)"
+    );
+}
+
+#[test]
+fn watermark_ratios() {
+    let watermark = |w_ratio, h_ratio| Filter::Watermark {
+        image_url: "logo.png".to_string(),
+        x: 10,
+        y: 20,
+        alpha: 50,
+        w_ratio,
+        h_ratio,
+    };
+
+    assert_eq!(
+        watermark(Some(30), Some(15)).to_string(),
+        "watermark(logo.png,10,20,50,30,15)"
+    );
+    assert_eq!(
+        watermark(Some(30), None).to_string(),
+        "watermark(logo.png,10,20,50,30)"
+    );
+    assert_eq!(
+        watermark(None, Some(15)).to_string(),
+        "watermark(logo.png,10,20,50,none,15)"
+    );
+}
+
+#[test]
+fn watermark_tiled() {
+    let filter = Filter::from(WatermarkTiled {
+        image_url: "http://my.server.com/logo_(dark).png".to_string(),
+        alpha: 50,
+        w_ratio: None,
+        h_ratio: Some(15),
+        x_spacing: 100,
+        y_spacing: 200,
+    });
+
+    assert_eq!(
+        filter,
+        Filter::Custom {
+            name: "watermark".to_string(),
+            args: [
+                "http://my.server.com/logo_%28dark%29.png",
+                "repeat",
+                "repeat",
+                "50",
+                "none",
+                "15",
+                "100",
+                "200",
+            ]
+            .map(String::from)
+            .to_vec(),
+        }
+    );
+
+    let filter = Filter::from(WatermarkTiled {
+        image_url: "https://cdn.example.com/w_100,h_50/logo.png".to_string(),
+        alpha: 50,
+        w_ratio: None,
+        h_ratio: None,
+        x_spacing: 10,
+        y_spacing: 10,
+    });
+    assert_eq!(
+        filter.to_string(),
+        "watermark(https://cdn.example.com/w_100%2Ch_50/logo.png,repeat,repeat,50,none,none,10,10)"
+    );
+}
+
+#[test]
+fn watermark_url_commas_are_encoded() {
+    let filter = Filter::watermark("https://cdn.example.com/w_100,h_50/logo.png", -10, 20, 50);
+
+    assert_eq!(
+        filter.args(),
+        [
+            "https://cdn.example.com/w_100%2Ch_50/logo.png",
+            "-10",
+            "20",
+            "50"
+        ]
+    );
+    assert_eq!(
+        filter.to_string(),
+        "watermark(https://cdn.example.com/w_100%2Ch_50/logo.png,-10,20,50)"
+    );
+    assert_eq!(
+        filter.to_string().parse::<Filter>().ok(),
+        Some(Filter::watermark(
+            "https://cdn.example.com/w_100%2Ch_50/logo.png",
+            -10,
+            20,
+            50
+        ))
+    );
+}
+
+#[test]
+fn filter_round_trip() {
+    let filters = [
+        Filter::AutoJPG,
+        Filter::BackgroundColor(Color::Rgb(255, 0, 16)),
+        Filter::BackgroundColor(Color::Auto),
+        Filter::Blur {
+            radius: 7,
+            sigma: None,
+        },
+        Filter::Blur {
+            radius: 7,
+            sigma: Some(3),
+        },
+        Filter::Brightness(-10),
+        Filter::Contrast(20),
+        Convolution::edge_detect3x3(),
+        Filter::Cover,
+        Filter::Equalize,
+        Filter::ExtractFocalPoints,
+        Filter::Fill {
+            color: Color::from("blue"),
+            fill_transparent: true,
+        },
+        Filter::Focal(Rect::new(10, 20, 30, 40)),
+        Filter::Format(Format::Webp),
+        Filter::Grayscale,
+        Filter::MaxBytes(u64::from(u32::MAX) + 1),
+        Filter::MaxAge(3600),
+        Filter::NoUpscale,
+        Filter::Noise(40),
+        Filter::Proportion(0.5),
+        Filter::Quality(80),
+        Filter::RedEye,
+        Filter::Rgb {
+            r_amount: 10,
+            g_amount: -20,
+            b_amount: 30,
+        },
+        Filter::Rotate(90),
+        Filter::RoundCorners {
+            radius: Radius::Ellipsis(20, 10),
+            color: Color::from("red"),
+            transparent: false,
+        },
+        Filter::Saturation(-50),
+        Filter::Sharpen {
+            sharpen_amount: 2.0,
+            sharpen_radius: 1.5,
+            luminance_only: true,
+        },
+        Filter::Stretch,
+        Filter::StripEXIF,
+        Filter::StripICC,
+        Filter::Upscale,
+        Filter::watermark("http://my.server.com/logo_(dark).png", -10, 20, 50),
+        Filter::Watermark {
+            image_url: "logo.png".to_string(),
+            x: 10,
+            y: 20,
+            alpha: 50,
+            w_ratio: None,
+            h_ratio: Some(15),
+        },
+        Filter::Custom {
+            name: "my_filter".to_string(),
+            args: vec!["a".to_string(), "b".to_string()],
+        },
+    ];
+
+    for filter in filters {
+        assert_eq!(filter.to_string().parse::<Filter>().ok(), Some(filter));
+    }
+
+    for invalid in [
+        "",
+        "blur",
+        "blur()",
+        "blur(1,2,3)",
+        "grayscale(1)",
+        "quality(101x)",
+        "fill(not a color)",
+        "(1)",
+    ] {
+        assert!(invalid.parse::<Filter>().is_err(), "{invalid}");
+    }
+}
+
+#[test]
+fn parse_pipeline() {
+    let filters = Filter::parse_pipeline(
+        "grayscale():watermark(http://my.server.com/logo_%28dark%29.png,-10,20,50):quality(80)",
+    )
+    .expect("Parsing failed");
+
+    assert_eq!(
+        filters,
+        [
+            Filter::Grayscale,
+            Filter::watermark("http://my.server.com/logo_(dark).png", -10, 20, 50),
+            Filter::Quality(80),
+        ]
+    );
+
+    assert_eq!(
+        Filter::parse_pipeline("filters:focal(10x20:30x40):max_age(60)").ok(),
+        Some(vec![
+            Filter::Focal(Rect::new(10, 20, 30, 40)),
+            Filter::MaxAge(60)
+        ])
+    );
+    assert_eq!(Filter::parse_pipeline("").ok(), Some(vec![]));
+    assert_eq!(
+        Filter::parse_pipeline("grayscale():blur()"),
+        Err(FilterError::Invalid("blur()".to_string()))
+    );
+}
+
+#[test]
+fn parse_url_with_invalid_filters() {
+    assert!(matches!(
+        Endpoint::from_url("http://my.server.com/unsafe/300x200/filters:quality(abc)/a.jpg"),
+        Err(Error::Filter(FilterError::Invalid(filter))) if filter == "quality(abc)"
+    ));
+    assert!(matches!(
+        Endpoint::from_url("/unsafe/filters:grayscale()"),
+        Err(Error::InvalidUrl(_))
+    ));
+}
+
+#[test]
+fn convolution_presets() {
+    assert_eq!(
+        Convolution::blur3x3().to_string(),
+        "convolution(1;2;1;2;4;2;1;2;1,3,true)"
+    );
+    assert_eq!(
+        Convolution::box_blur3x3().to_string(),
+        "convolution(1;1;1;1;1;1;1;1;1,3,true)"
+    );
+    assert_eq!(
+        Convolution::sharpen3x3().to_string(),
+        "convolution(0;-1;0;-1;5;-1;0;-1;0,3,false)"
+    );
+    assert_eq!(
+        Convolution::edge_detect3x3().to_string(),
+        "convolution(-1;-1;-1;-1;8;-1;-1;-1;-1,3,false)"
+    );
+    assert_eq!(
+        Convolution::emboss3x3().to_string(),
+        "convolution(-2;-1;0;-1;1;1;0;1;2,3,false)"
+    );
+}
+
+#[test]
+fn boolean_arguments() {
+    let sharpen = |luminance_only| Filter::Sharpen {
+        sharpen_amount: 2.0,
+        sharpen_radius: 1.0,
+        luminance_only,
+    };
+    assert_eq!(sharpen(true).to_string(), "sharpen(2,1,true)");
+    assert_eq!(sharpen(false).to_string(), "sharpen(2,1,false)");
+
+    let convolution = |should_normalize| Filter::Convolution {
+        matrix_items: vec![1, 1, 1],
+        number_of_columns: 3,
+        should_normalize,
+    };
+    assert_eq!(convolution(true).to_string(), "convolution(1;1;1,3,true)");
+    assert_eq!(convolution(false).to_string(), "convolution(1;1;1,3,false)");
+
+    let fill = |fill_transparent| Filter::Fill {
+        color: Color::from("red"),
+        fill_transparent,
+    };
+    assert_eq!(fill(true).to_string(), "fill(red,1)");
+    assert_eq!(fill(false).to_string(), "fill(red)");
+
+    let round_corners = |transparent| Filter::RoundCorners {
+        radius: Radius::Circle(20),
+        color: Color::from("red"),
+        transparent,
+    };
+    assert_eq!(round_corners(true).to_string(), "roundcorners(20,red,1)");
+    assert_eq!(round_corners(false).to_string(), "roundcorners(20,red)");
+}
+
+#[test]
+fn format_mime_types() {
+    for (format, mime_type) in [
+        (Format::Webp, "image/webp"),
+        (Format::Jpeg, "image/jpeg"),
+        (Format::Gif, "image/gif"),
+        (Format::Png, "image/png"),
+        (Format::Avif, "image/avif"),
+        (Format::Heic, "image/heic"),
+    ] {
+        assert_eq!(format.mime_type(), mime_type);
+        assert_eq!(
+            Filter::Format(format).to_string(),
+            format!("format({})", &mime_type["image/".len()..])
+        );
+    }
+}
+
+#[test]
+fn quality_for_format() {
+    let qualities = [
+        (Format::Jpeg, 82),
+        (Format::Webp, 80),
+        (Format::Avif, 60),
+        (Format::Heic, 60),
+        (Format::Png, 80),
+        (Format::Gif, 80),
+    ];
+    for (format, quality) in qualities {
+        assert_eq!(Filter::quality_for(format), Filter::Quality(quality));
+    }
+
+    let endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .filter(Filter::Format(Format::Webp))
+        .filter(Filter::quality_for(Format::Webp))
+        .build();
+    assert_eq!(
+        endpoint.to_path(IMAGE_PATH),
+        format!("/unsafe/filters:format(webp):quality(80)/{IMAGE_PATH}")
+    );
+}
+
+#[test]
+fn delivery_options() {
+    let delivery = DeliveryOptions {
+        quality: Some(80),
+        format: Some(Format::Webp),
+        max_age: Some(3600),
+        ..Default::default()
+    };
+    let path = new_builder()
+        .filters(delivery.filters().expect("Invalid delivery options"))
+        .build()
+        .to_path(IMAGE_PATH);
+    assert!(path.contains("/filters:quality(80):format(webp):max_age(3600)/"));
+
+    let delivery = DeliveryOptions {
+        max_bytes: Some(2000),
+        max_age: Some(60),
+        ..Default::default()
+    };
+    assert_eq!(
+        delivery.filters().ok(),
+        Some(vec![Filter::MaxBytes(2000), Filter::MaxAge(60)])
+    );
+
+    assert_eq!(DeliveryOptions::default().filters().ok(), Some(vec![]));
+
+    let delivery = DeliveryOptions {
+        quality: Some(101),
+        ..Default::default()
+    };
+    assert_eq!(
+        delivery.filters(),
+        Err(FilterError::OutOfRange {
+            argument: "quality",
+            value: 101,
+            min: 0,
+            max: 100,
+        })
+    );
+}
+
+#[test]
+fn dedup_single_valued_filters() {
+    let endpoint = new_builder()
+        .filters([
+            Filter::MaxAge(60),
+            Filter::MaxBytes(1000),
+            Filter::Brightness(10),
+            Filter::MaxAge(3600),
+            Filter::Brightness(20),
+            Filter::MaxBytes(2000),
+        ])
+        .dedup_filters(true)
+        .build();
+
+    let path = endpoint.to_path(IMAGE_PATH);
+
+    assert!(path.contains("/filters:brightness(10):max_age(3600):brightness(20):max_bytes(2000)/"));
+}
+
+#[test]
+fn rect_dimensions() {
+    let rect = Rect::new(10, 20, 310, 220);
+
+    assert_eq!(rect.width(), 300);
+    assert_eq!(rect.height(), 200);
+}
+
+#[test]
+fn geometry_getters() {
+    let point = Point::new(-10, 20);
+    assert_eq!((point.x(), point.y()), (-10, 20));
+
+    let rect = Rect::new(10, 20, 310, 220);
+    assert_eq!(
+        (rect.left(), rect.top(), rect.right(), rect.bottom()),
+        (10, 20, 310, 220)
+    );
+}
+
+#[test]
+fn point_distances() {
+    let a = Point::new(1, 2);
+    let b = Point::new(4, -2);
+
+    #[cfg(feature = "std")]
+    assert!((a.euclidean(&b) - 5.0).abs() < f64::EPSILON);
+    assert_eq!(a.manhattan(&b), 7);
+    assert_eq!(b.manhattan(&a), 7);
+    assert_eq!(a.manhattan(&a), 0);
+
+    let min = Point::new(i32::MIN, i32::MIN);
+    let max = Point::new(i32::MAX, i32::MAX);
+    assert_eq!(min.manhattan(&max), 2 * u64::from(u32::MAX));
+}
+
+#[test]
+fn rect_scale_clamped() {
+    let rect = Rect::new(0, 0, 100, 100);
+
+    assert_eq!(rect.scale_clamped(0.5), Rect::new(25, 25, 75, 75));
+    assert_eq!(rect.scale_clamped(2.0), Rect::new(-50, -50, 150, 150));
+    assert_eq!(rect.scale(-1.0), Rect::new(100, 100, 0, 0));
+    assert_eq!(rect.scale_clamped(-1.0), rect);
+
+    let scaled = Rect::new(10, 20, 50, 40).scale_clamped(-2.0);
+    assert_eq!(scaled, Rect::new(-10, 10, 70, 50));
+    assert!(scaled.width() > 0 && scaled.height() > 0);
+}
+
+#[test]
+fn rect_intersection_and_union() {
+    let a = Rect::new(0, 0, 100, 100);
+
+    let overlapping = Rect::new(50, 20, 150, 80);
+    assert_eq!(
+        a.intersection(&overlapping),
+        Some(Rect::new(50, 20, 100, 80))
+    );
+    assert_eq!(a.union(&overlapping), Rect::new(0, 0, 150, 100));
+
+    let disjoint = Rect::new(200, 200, 300, 300);
+    assert_eq!(a.intersection(&disjoint), None);
+    assert_eq!(a.union(&disjoint), Rect::new(0, 0, 300, 300));
+
+    let touching = Rect::new(100, 0, 200, 100);
+    assert_eq!(a.intersection(&touching), None);
+
+    let contained = Rect::new(10, 10, 20, 20);
+    assert_eq!(a.intersection(&contained), Some(contained));
+    assert_eq!(contained.intersection(&a), Some(contained));
+    assert_eq!(a.union(&contained), a);
+}
+
+#[test]
+fn signing_never_panics() {
+    let keys = [String::new(), SECURITY_KEY.to_string(), "k".repeat(1024)];
+
+    for key in keys {
+        let server = Server::new(TEST_BASE, key).expect("Any key length is valid for HMAC");
+        let endpoint = server.endpoint_builder().resize((300, 200)).build();
+
+        let path = endpoint.to_path(IMAGE_PATH);
+
+        assert!(path.ends_with(&format!("=/300x200/{IMAGE_PATH}")));
+    }
+}
+
+#[test]
+fn unset_resize_is_omitted() {
+    let endpoint = new_builder().resize(Resize::new(None, None)).build();
+    let expected = new_builder().build();
+
+    assert_eq!(endpoint.to_path(IMAGE_PATH), expected.to_path(IMAGE_PATH));
+
+    let endpoint = new_builder()
+        .resize(Resize::new(None, None).flip_vertically())
+        .build();
+
+    assert!(endpoint.to_path(IMAGE_PATH).contains("/x-/"));
+}
+
+#[test]
+fn resize_square() {
+    let endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .resize_square(200)
+        .build();
+    assert_eq!(
+        endpoint.to_path(IMAGE_PATH),
+        format!("/unsafe/200x200/{IMAGE_PATH}")
+    );
+
+    let flipped = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .resize_square(-200)
+        .build();
+    assert_eq!(
+        flipped.to_path(IMAGE_PATH),
+        format!("/unsafe/-200x-200/{IMAGE_PATH}")
+    );
+}
+
+#[test]
+fn filter_variants() {
+    let variants = Filter::variants().collect::<Vec<_>>();
+
+    for name in [
+        "brightness",
+        "watermark",
+        "backgroundcolor",
+        "extractfocalpoints",
+        "roundcorners",
+        "stripexif",
+        "noupscale",
+    ] {
+        assert!(variants.contains(&name), "{name}");
+    }
+    assert!(!variants.contains(&"custom"));
+}
+
+#[test]
+fn maybe_setters() {
+    let endpoint = new_builder()
+        .maybe_response(None)
+        .maybe_trim(None::<Trim>)
+        .maybe_crop(None::<Rect>)
+        .build();
+    assert_eq!(
+        endpoint.to_path(IMAGE_PATH),
+        new_builder().build().to_path(IMAGE_PATH)
+    );
+
+    let endpoint = new_builder()
+        .maybe_response(Some(ResponseMode::Debug))
+        .maybe_crop(Some(Rect::new(10, 20, 300, 400)))
+        .build();
+    let expected = new_builder()
+        .debug()
+        .crop(Rect::new(10, 20, 300, 400))
+        .build();
+    assert_eq!(endpoint.to_path(IMAGE_PATH), expected.to_path(IMAGE_PATH));
+}
+
+#[test]
+fn resize_upscale() {
+    let endpoint = new_builder()
+        .resize_upscale((1920, 1080))
+        .filter(Filter::Grayscale)
+        .build();
+    let expected = new_builder()
+        .fit_in(FitIn::Default)
+        .resize((1920, 1080))
+        .filters([Filter::Upscale, Filter::Grayscale])
+        .build();
+
+    let path = endpoint.to_path(IMAGE_PATH);
+    assert_eq!(path, expected.to_path(IMAGE_PATH));
+    assert!(path.contains("/fit-in/1920x1080/filters:upscale():grayscale()/"));
+}
+
+#[test]
+fn fit_and_fill() {
+    let endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .fit_and_fill((300, 300), "blue")
+        .build();
+    assert_eq!(
+        endpoint.to_path(IMAGE_PATH),
+        format!("/unsafe/fit-in/300x300/filters:fill(blue)/{IMAGE_PATH}")
+    );
+
+    let endpoint = new_builder()
+        .fit_and_fill((300, 300), Color::Auto)
+        .smart(true)
+        .build();
+    let expected = new_builder()
+        .fit_in(FitIn::Default)
+        .resize((300, 300))
+        .smart(true)
+        .filter(Filter::Fill {
+            color: Color::Auto,
+            fill_transparent: false,
+        })
+        .build();
+    assert_eq!(endpoint.to_path(IMAGE_PATH), expected.to_path(IMAGE_PATH));
+}
+
+#[test]
+fn resize_dpr() {
+    let resize_dpr = |css_width, css_height, dpr| {
+        new_builder()
+            .resize_dpr(css_width, css_height, dpr)
+            .expect("valid device pixel ratio")
+            .build()
+            .to_path(IMAGE_PATH)
+    };
+    let resize = |width, height| {
+        new_builder()
+            .resize((width, height))
+            .build()
+            .to_path(IMAGE_PATH)
+    };
+
+    assert_eq!(resize_dpr(150, 100, 2.0), resize(300, 200));
+    assert_eq!(resize_dpr(101, 33, 3.0), resize(303, 99));
+    assert_eq!(resize_dpr(101, 0, 1.5), resize(152, 0));
+    assert_eq!(resize_dpr(33, 67, 1.333), resize(44, 89));
+}
+
+#[test]
+fn resize_dpr_rejects_invalid_ratios() {
+    for dpr in [0.0, -1.0, f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+        assert!(
+            matches!(
+                new_builder().resize_dpr(150, 100, dpr),
+                Err(Error::InvalidDpr(_))
+            ),
+            "{dpr}"
+        );
+    }
+}
+
+#[test]
+fn try_to_path_signed() {
+    let endpoint = new_builder().build();
+    assert_eq!(
+        endpoint.try_to_path(IMAGE_PATH).ok(),
+        Some(endpoint.to_path(IMAGE_PATH))
+    );
+    assert_eq!(
+        endpoint.try_to_url(IMAGE_PATH).ok(),
+        Some(endpoint.to_url(IMAGE_PATH))
+    );
+}
+
+#[test]
+#[cfg(feature = "deny-unsafe")]
+fn deny_unsafe() {
+    let endpoint = Server::new_unsafe(TEST_BASE).endpoint_builder().build();
+    assert!(matches!(
+        endpoint.try_to_path(IMAGE_PATH),
+        Err(Error::UnsafeNotAllowed)
+    ));
+    assert!(matches!(
+        endpoint.try_to_url(IMAGE_PATH),
+        Err(Error::UnsafeNotAllowed)
+    ));
+    // The infallible methods are left alone, as the feature may come from another crate.
+    assert_eq!(
+        endpoint.to_path(IMAGE_PATH),
+        format!("/unsafe/{IMAGE_PATH}")
+    );
+
+    let endpoint = Server::new_unsigned(TEST_BASE).endpoint_builder().build();
+    assert!(matches!(
+        endpoint.try_to_path(IMAGE_PATH),
+        Err(Error::UnsafeNotAllowed)
+    ));
+
+    let endpoint = new_builder().build();
+    assert_eq!(
+        endpoint.try_to_path(IMAGE_PATH).ok(),
+        Some(endpoint.to_path(IMAGE_PATH))
+    );
+}
+
+#[test]
+fn unsigned_server_has_no_security_segment() {
+    let server = Server::new_unsigned(TEST_BASE);
+    let endpoint = server
+        .endpoint_builder()
+        .resize((300, 200))
+        .filter(Filter::Grayscale)
+        .build();
+
+    let path = endpoint.to_path(IMAGE_PATH);
+    assert_eq!(path, format!("/300x200/filters:grayscale()/{IMAGE_PATH}"));
+    assert!(!path.contains("unsafe"));
+    assert_eq!(endpoint.to_path_generic(IMAGE_PATH), path);
+    assert_eq!(endpoint.compiled().path(IMAGE_PATH), path);
+    assert_eq!(
+        endpoint.estimated_url_len(IMAGE_PATH),
+        endpoint.to_url(IMAGE_PATH).len()
+    );
+    assert!(server.verify_url(&endpoint.to_url(IMAGE_PATH)));
+
+    assert_eq!(
+        server.endpoint_builder().build().to_path(IMAGE_PATH),
+        format!("/{IMAGE_PATH}")
+    );
+    assert_eq!(
+        server
+            .with_base_path("images")
+            .endpoint_builder()
+            .build()
+            .to_url("a.jpg"),
+        format!("{TEST_BASE}/images/a.jpg")
+    );
+    assert_ne!(
+        Server::new_unsigned(TEST_BASE),
+        Server::new_unsafe(TEST_BASE)
+    );
+}
+
+#[test]
+fn focal_point_weight_and_origin() {
+    let point: FocalPoint = serde_json::from_str(
+        r#"{"x": 100, "y": 50, "z": 0.75, "height": 20, "width": 40, "origin": "face"}"#,
+    )
+    .expect("Invalid focal point");
+    assert_eq!(
+        (point.x, point.y, point.width, point.height),
+        (100, 50, 40, 20)
+    );
+    assert_eq!(point.z, Some(0.75));
+    assert_eq!(point.origin.as_deref(), Some("face"));
+    assert_eq!(Rect::from(point), Rect::new(80, 40, 120, 60));
+
+    let point: FocalPoint = serde_json::from_str(r#"{"x": 1, "y": 2, "height": 3, "width": 4}"#)
+        .expect("Invalid focal point");
+    assert_eq!(point.z, None);
+    assert_eq!(point.origin, None);
+    assert_eq!(
+        serde_json::to_string(&point).expect("serialization failed"),
+        r#"{"x":1,"y":2,"height":3,"width":4}"#
+    );
+}
+
+#[test]
+fn metadata_round_trip() {
+    let data = include_str!("metadata.json");
+    let meta: Meta = serde_json::from_str(data).expect("Invalid metadata");
+
+    assert_eq!(
+        meta.thumbor.operations,
+        [
+            Operation::Crop(Rect::new(10, 10, 300, 200)),
+            Operation::Resize(Point::new(300, 200)),
+            Operation::FlipHorizontally,
+            Operation::FlipVertically,
+        ]
+    );
+
+    let original: serde_json::Value = serde_json::from_str(data).expect("Invalid JSON");
+    let operations = serde_json::to_value(&meta.thumbor.operations).expect("Serialization failed");
+    assert_eq!(operations, original["thumbor"]["operations"]);
+
+    let reparsed: Vec<Operation> =
+        serde_json::from_value(operations).expect("Deserialization failed");
+    assert_eq!(reparsed, meta.thumbor.operations);
+
+    let endpoint = meta.to_endpoint(Server::new_unsafe(TEST_BASE).endpoint_builder());
+    assert_eq!(
+        endpoint.to_path(&meta.thumbor.source.url),
+        "/unsafe/10x10:300x200/-300x-200/path/to/my/nice/image.jpg"
+    );
+}
+
+#[test]
+fn nested_thumbor_uri_is_opaque() {
+    use base64ct::{Base64Url, Encoding};
+    use hmac::Mac;
+
+    let nested = "thumbor.example.com/unsafe/100x100/filters:grayscale():quality(80)/a.jpg";
+    let server = Server::new(TEST_BASE, SECURITY_KEY).expect("Server creation failed");
+    let endpoint = server
+        .endpoint_builder()
+        .resize((300, 200))
+        .filter(Filter::Format(Format::Webp))
+        .build();
+
+    let path = endpoint.to_path(nested);
+    let signed = format!("300x200/filters:format(webp)/{nested}");
+    let mut mac =
+        crate::server::HmacSha1::new_from_slice(SECURITY_KEY.as_bytes()).expect("Invalid key");
+    mac.update(signed.as_bytes());
+    let signature = Base64Url::encode_string(&mac.finalize().into_bytes());
+
+    assert_eq!(path, format!("/{signature}/{signed}"));
+    assert_eq!(path, endpoint.compiled().path(nested));
+    assert!(server.verify_url(&endpoint.to_url(nested)));
+
+    let (parsed, image_uri) = Endpoint::from_url(&endpoint.to_url(nested)).expect("Invalid URL");
+    assert_eq!(image_uri, nested);
+    assert_eq!(
+        parsed.to_path(&image_uri),
+        "/unsafe/300x200/filters:format(webp)/".to_string() + nested
+    );
+
+    let encoded = server
+        .endpoint_builder()
+        .resize((300, 200))
+        .uri_encoding(UriEncoding::Encode)
+        .build()
+        .to_path(nested);
+    assert!(encoded.ends_with(
+        "/300x200/thumbor.example.com/unsafe/100x100/filters:grayscale%28%29:quality%2880%29/a.jpg"
+    ));
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn image_crate_conversions() {
+    let image = image::RgbImage::new(800, 600);
+    assert_eq!(Point::from(&image), Point::new(800, 600));
+
+    let dynamic = image::DynamicImage::new_luma8(300, 200);
+    let endpoint = Server::new_unsafe(TEST_BASE)
+        .endpoint_builder()
+        .resize(Point::from(&dynamic))
+        .build();
+    assert_eq!(endpoint.to_path("a.jpg"), "/unsafe/300x200/a.jpg");
+
+    let rect = image::math::Rect {
+        x: 10,
+        y: 20,
+        width: 100,
+        height: 50,
+    };
+    assert_eq!(Rect::from(rect), Rect::new(10, 20, 110, 70));
+
+    let huge = image::math::Rect {
+        x: u32::MAX,
+        y: 0,
+        width: 1,
+        height: 1,
+    };
+    assert_eq!(Rect::from(huge), Rect::new(i32::MAX, 0, i32::MAX, 1));
+}
+
+#[test]
+fn server_equality() {
+    let server = || Server::new(TEST_BASE, SECURITY_KEY).expect("Server creation failed");
+
+    assert_eq!(server(), server());
+    assert_ne!(
+        server(),
+        Server::new(TEST_BASE, "another-key").expect("Server creation failed")
+    );
+    assert_ne!(
+        server(),
+        Server::new("http://another.server.com", SECURITY_KEY).expect("Server creation failed")
+    );
+    assert_ne!(server(), Server::new_unsafe(TEST_BASE));
+    assert_eq!(Server::new_unsafe(TEST_BASE), Server::new_unsafe(TEST_BASE));
+}
+
+#[test]
+fn server_debug_redacts_key() {
+    let server = Server::new(TEST_BASE, SECURITY_KEY).expect("Server creation failed");
+    let debug = format!("{server:?}");
+
+    assert!(debug.contains(TEST_BASE));
+    assert!(debug.contains("<redacted>"));
+    assert!(!debug.contains(SECURITY_KEY));
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_filter_path() {
+    let color: crate::endpoint::filter::Color = Color::from("red");
+    assert_eq!(
+        crate::endpoint::filter::Filter::BackgroundColor(color),
+        Filter::BackgroundColor(Color::from("red"))
+    );
+}
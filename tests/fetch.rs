@@ -0,0 +1,48 @@
+#![cfg(feature = "reqwest")]
+
+use thumbor::{error::Error, Server};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const METADATA: &str = include_str!("../src/metadata.json");
+
+#[tokio::test]
+async fn fetch_metadata() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/unsafe/meta/300x200/path/to/my/nice/image.jpg"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(METADATA))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let server = Server::new_unsafe(mock_server.uri());
+    let endpoint = server
+        .endpoint_builder()
+        .metadata()
+        .resize((300, 200))
+        .build();
+
+    let meta = endpoint
+        .fetch_metadata(&reqwest::Client::new(), "path/to/my/nice/image.jpg")
+        .await
+        .expect("Fetching metadata failed");
+
+    assert_eq!(meta.thumbor.source.url, "path/to/my/nice/image.jpg");
+    assert_eq!(meta.thumbor.operations.len(), 4);
+}
+
+#[tokio::test]
+async fn fetch_metadata_requires_meta() {
+    let server = Server::new_unsafe("http://localhost:8888");
+    let endpoint = server.endpoint_builder().build();
+
+    let result = endpoint
+        .fetch_metadata(&reqwest::Client::new(), "image.jpg")
+        .await;
+
+    assert!(matches!(result, Err(Error::NotMetadata)));
+}